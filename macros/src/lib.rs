@@ -0,0 +1,532 @@
+// abigen! generates typed contract bindings from an ABI JSON file
+extern crate proc_macro;
+
+use ethabi::param_type::{ParamType, Reader};
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{format_ident, quote};
+use serde_json::Value;
+use std::{env, fs, path::Path};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, Ident, LitStr, Token,
+};
+
+// methods generated on every binding, which ABI functions must not shadow
+const RESERVED: [&str; 5] = [
+    "new",
+    "from_contract",
+    "contract",
+    "contract_mut",
+    "invalid",
+];
+
+struct Input {
+    name: Ident,
+    path: LitStr,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let path = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Input { name, path })
+    }
+}
+
+struct Param {
+    name: String,
+    kind: ParamType,
+    indexed: bool,
+}
+
+struct Function {
+    name: String,
+    inputs: Vec<Param>,
+    outputs: Vec<Param>,
+    constant: bool,
+    payable: bool,
+}
+
+struct Event {
+    name: String,
+    inputs: Vec<Param>,
+}
+
+// abigen!(Name, "path/to/abi.json") generates `Name<C: Client>`, a wrapper of
+// `contract::Contract<C>` with one typed method per ABI function and, for each
+// ABI event, a `Name<Event>` struct with its `<event>_events` receipt decoder.
+// The path is relative to the directory of the calling crate's Cargo.toml.
+// Parameter names that collide get a numeric suffix; overloaded events are
+// rejected as their structs would share a name.
+#[proc_macro]
+pub fn abigen(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as Input);
+    match expand(&input) {
+        Ok(r) => r.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn error(span: Span, msg: String) -> syn::Error {
+    syn::Error::new(span, msg)
+}
+
+fn expand(input: &Input) -> syn::Result<TokenStream> {
+    let span = input.path.span();
+    let dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = Path::new(&dir).join(input.path.value());
+    let json = match fs::read_to_string(&path) {
+        Ok(r) => r,
+        Err(e) => {
+            return Err(error(
+                span,
+                format!("failed to read {}: {}", path.display(), e),
+            ))
+        }
+    };
+    let entries: Vec<Value> = match serde_json::from_str(&json) {
+        Ok(r) => r,
+        Err(e) => return Err(error(span, format!("invalid ABI JSON: {}", e))),
+    };
+
+    let mut functions: Vec<Function> = Vec::new();
+    let mut events: Vec<Event> = Vec::new();
+    for entry in entries.iter() {
+        let name = String::from(entry["name"].as_str().unwrap_or_default());
+        match entry["type"].as_str() {
+            // entries without a type are functions, as in ethabi
+            Some("function") | None => {
+                let mutability = entry["stateMutability"].as_str().unwrap_or_default();
+                let function = Function {
+                    inputs: parse_params(span, &entry["inputs"])?,
+                    outputs: parse_params(span, &entry["outputs"])?,
+                    constant: entry["constant"].as_bool().unwrap_or(false)
+                        || mutability == "view"
+                        || mutability == "pure",
+                    payable: entry["payable"].as_bool().unwrap_or(false) || mutability == "payable",
+                    name,
+                };
+                // the last overload wins, like in ethabi::Contract
+                functions.retain(|f| f.name != function.name);
+                functions.push(function);
+            }
+            Some("event") if !entry["anonymous"].as_bool().unwrap_or(false) => {
+                // event structs and decoders are named after the event only
+                if events.iter().any(|e| e.name == name) {
+                    return Err(error(
+                        span,
+                        format!("overloaded event {} is not supported", name),
+                    ));
+                }
+                events.push(Event {
+                    inputs: parse_params(span, &entry["inputs"])?,
+                    name,
+                });
+            }
+            _ => (),
+        }
+    }
+
+    let name = &input.name;
+    let abs_path = path.to_string_lossy().to_string();
+    let methods = functions.iter().map(function_method);
+    let decoders = events.iter().map(|e| event_decoder(name, e));
+    let structs = events.iter().map(|e| event_struct(name, e));
+    Ok(quote! {
+        pub struct #name<C: ::iotex_antenna_rust::rpc::Client> {
+            contract: ::iotex_antenna_rust::contract::Contract<C>,
+        }
+
+        impl<C: ::iotex_antenna_rust::rpc::Client> #name<C> {
+            pub const ABI: &'static str = include_str!(#abs_path);
+
+            pub fn new<S: ::iotex_antenna_rust::account::Signer + 'static>(
+                address: ::iotex_antenna_rust::address::v1::AddrV1,
+                client: C,
+                signer: S,
+            ) -> Result<Self, ::iotex_antenna_rust::contract::ContractError> {
+                let contract = ::iotex_antenna_rust::contract::Contract::new(
+                    address, Self::ABI, client, signer,
+                )?;
+                Ok(#name { contract })
+            }
+
+            pub fn from_contract(contract: ::iotex_antenna_rust::contract::Contract<C>) -> Self {
+                #name { contract }
+            }
+
+            pub fn contract(&self) -> &::iotex_antenna_rust::contract::Contract<C> {
+                &self.contract
+            }
+
+            pub fn contract_mut(&mut self) -> &mut ::iotex_antenna_rust::contract::Contract<C> {
+                &mut self.contract
+            }
+
+            fn invalid() -> ::iotex_antenna_rust::contract::ContractError {
+                ::iotex_antenna_rust::contract::ContractError::AbiError(
+                    ::iotex_antenna_rust::ethabi::ErrorKind::InvalidData.into(),
+                )
+            }
+
+            #(#methods)*
+
+            #(#decoders)*
+        }
+
+        #(#structs)*
+    })
+}
+
+fn parse_params(span: Span, value: &Value) -> syn::Result<Vec<Param>> {
+    let mut params = Vec::new();
+    for p in value.as_array().map(|v| v.as_slice()).unwrap_or_default() {
+        let ty = p["type"].as_str().unwrap_or_default();
+        if ty.starts_with("tuple") {
+            return Err(error(
+                span,
+                String::from("tuple parameters are not supported"),
+            ));
+        }
+        let kind = match Reader::read(ty) {
+            Ok(r) => r,
+            Err(e) => return Err(error(span, format!("invalid type {}: {}", ty, e))),
+        };
+        params.push(Param {
+            name: String::from(p["name"].as_str().unwrap_or_default()),
+            kind,
+            indexed: p["indexed"].as_bool().unwrap_or(false),
+        });
+    }
+    Ok(params)
+}
+
+// snake_case converts a Solidity identifier (balanceOf, tokenURI, ...) to snake case
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut res = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                res.push('_');
+            }
+        }
+        res.extend(c.to_lowercase());
+    }
+    res
+}
+
+// ident turns name into a valid identifier, falling back to fallback when it is
+// empty and escaping keywords and reserved names with a trailing underscore
+fn ident(name: &str, fallback: String) -> Ident {
+    let mut name = snake_case(name.trim_start_matches('_'));
+    if name.is_empty() {
+        name = fallback;
+    }
+    if RESERVED.contains(&name.as_str()) || syn::parse_str::<Ident>(&name).is_err() {
+        name.push('_');
+    }
+    format_ident!("{}", name)
+}
+
+// param_idents names params with ident, adding a numeric suffix to names that
+// are already taken, e.g. by `to` and `_to`
+fn param_idents(params: &[Param], fallback: &str) -> Vec<Ident> {
+    let mut idents: Vec<Ident> = Vec::new();
+    for (i, p) in params.iter().enumerate() {
+        let name = ident(&p.name, format!("{}{}", fallback, i));
+        let name = unique(name, &idents);
+        idents.push(name);
+    }
+    idents
+}
+
+// unique returns name, or name_<n> with the smallest n not in taken
+fn unique(name: Ident, taken: &[Ident]) -> Ident {
+    if !taken.contains(&name) {
+        return name;
+    }
+    (1usize..)
+        .map(|n| format_ident!("{}_{}", name, n))
+        .find(|r| !taken.contains(r))
+        .expect("unbounded suffixes")
+}
+
+fn rust_type(kind: &ParamType) -> TokenStream {
+    match kind {
+        ParamType::Address => quote!(::iotex_antenna_rust::ethabi::Address),
+        ParamType::Bytes => quote!(Vec<u8>),
+        ParamType::Int(_) | ParamType::Uint(_) => quote!(::iotex_antenna_rust::ethabi::Uint),
+        ParamType::Bool => quote!(bool),
+        ParamType::String => quote!(String),
+        ParamType::FixedBytes(n) => {
+            let n = Literal::usize_unsuffixed(*n);
+            quote!([u8; #n])
+        }
+        ParamType::Array(inner) => {
+            let inner = rust_type(inner);
+            quote!(Vec<#inner>)
+        }
+        ParamType::FixedArray(inner, n) => {
+            let inner = rust_type(inner);
+            let n = Literal::usize_unsuffixed(*n);
+            quote!([#inner; #n])
+        }
+    }
+}
+
+// into_token builds the ethabi::Token of value, an expression of rust_type(kind)
+fn into_token(kind: &ParamType, value: TokenStream) -> TokenStream {
+    let token = quote!(::iotex_antenna_rust::ethabi::Token);
+    match kind {
+        ParamType::Address => quote!(#token::Address(#value)),
+        ParamType::Bytes => quote!(#token::Bytes(#value)),
+        ParamType::Int(_) => quote!(#token::Int(#value)),
+        ParamType::Uint(_) => quote!(#token::Uint(#value)),
+        ParamType::Bool => quote!(#token::Bool(#value)),
+        ParamType::String => quote!(#token::String(#value)),
+        ParamType::FixedBytes(_) => quote!(#token::FixedBytes(#value.to_vec())),
+        ParamType::Array(inner) => {
+            let inner = into_token(inner, quote!(x));
+            quote!(#token::Array(#value.into_iter().map(|x| #inner).collect()))
+        }
+        ParamType::FixedArray(inner, _) => {
+            let inner = into_token(inner, quote!(x));
+            quote!(#token::FixedArray(
+                ::std::iter::IntoIterator::into_iter(#value).map(|x| #inner).collect()
+            ))
+        }
+    }
+}
+
+// from_token converts token, an ethabi::Token expression, into rust_type(kind),
+// returning an invalid data error on mismatch
+fn from_token(kind: &ParamType, token: TokenStream) -> TokenStream {
+    let t = quote!(::iotex_antenna_rust::ethabi::Token);
+    let invalid = quote!(return Err(Self::invalid()));
+    match kind {
+        ParamType::Address => quote!(match #token { #t::Address(v) => v, _ => #invalid }),
+        ParamType::Bytes => quote!(match #token { #t::Bytes(v) => v, _ => #invalid }),
+        ParamType::Int(_) | ParamType::Uint(_) => {
+            quote!(match #token { #t::Int(v) | #t::Uint(v) => v, _ => #invalid })
+        }
+        ParamType::Bool => quote!(match #token { #t::Bool(v) => v, _ => #invalid }),
+        ParamType::String => quote!(match #token { #t::String(v) => v, _ => #invalid }),
+        ParamType::FixedBytes(n) => {
+            let n = Literal::usize_unsuffixed(*n);
+            quote!(match #token {
+                #t::FixedBytes(ref v) if v.len() == #n => {
+                    let mut a = [0u8; #n];
+                    a.copy_from_slice(v);
+                    a
+                }
+                _ => #invalid,
+            })
+        }
+        ParamType::Array(inner) => {
+            let ty = rust_type(inner);
+            let inner = from_token(inner, quote!(x));
+            quote!(match #token {
+                #t::Array(vs) | #t::FixedArray(vs) => vs
+                    .into_iter()
+                    .map(|x| -> Result<#ty, ::iotex_antenna_rust::contract::ContractError> {
+                        Ok(#inner)
+                    })
+                    .collect::<Result<Vec<#ty>, _>>()?,
+                _ => #invalid,
+            })
+        }
+        ParamType::FixedArray(inner, n) => {
+            let ty = rust_type(inner);
+            let vec = from_token(&ParamType::Array(inner.clone()), token);
+            let n = Literal::usize_unsuffixed(*n);
+            quote!({
+                let v: Vec<#ty> = #vec;
+                match <[#ty; #n] as ::std::convert::TryFrom<Vec<#ty>>>::try_from(v) {
+                    Ok(a) => a,
+                    Err(_) => #invalid,
+                }
+            })
+        }
+    }
+}
+
+fn function_method(function: &Function) -> TokenStream {
+    let method = ident(&function.name, String::from("function"));
+    let name = &function.name;
+    let args = param_idents(&function.inputs, "arg");
+    let types = function.inputs.iter().map(|p| rust_type(&p.kind));
+    let tokens = function
+        .inputs
+        .iter()
+        .zip(args.iter())
+        .map(|(p, a)| into_token(&p.kind, quote!(#a)));
+    let error = quote!(::iotex_antenna_rust::contract::ContractError);
+
+    if function.constant {
+        let outs: Vec<Ident> = (0..function.outputs.len())
+            .map(|i| format_ident!("out{}", i))
+            .collect();
+        let out_types: Vec<TokenStream> = function
+            .outputs
+            .iter()
+            .map(|p| rust_type(&p.kind))
+            .collect();
+        let decode = function.outputs.iter().zip(outs.iter()).map(|(p, o)| {
+            let value = from_token(&p.kind, quote!(t));
+            quote! {
+                let #o = match outputs.next() {
+                    Some(t) => #value,
+                    None => return Err(Self::invalid()),
+                };
+            }
+        });
+        let (ret, ret_type) = if outs.len() == 1 {
+            (quote!(#(#outs)*), quote!(#(#out_types)*))
+        } else {
+            (quote!((#(#outs),*)), quote!((#(#out_types),*)))
+        };
+        let call = quote!(self.contract.call(#name, &[#(#tokens),*])?);
+        let body = if outs.is_empty() {
+            quote!(#call;)
+        } else {
+            quote! {
+                let mut outputs = #call.into_iter();
+                #(#decode)*
+            }
+        };
+        quote! {
+            pub fn #method(&self, #(#args: #types),*) -> Result<#ret_type, #error> {
+                #body
+                Ok(#ret)
+            }
+        }
+    } else {
+        let (amount_arg, amount) = if function.payable {
+            // the value sent comes last, named amount unless an input is
+            let amount = unique(format_ident!("amount"), &args);
+            (
+                quote!(#amount: ::iotex_antenna_rust::ethabi::Uint),
+                quote!(#amount),
+            )
+        } else {
+            (quote!(), quote!(::iotex_antenna_rust::ethabi::Uint::zero()))
+        };
+        quote! {
+            pub fn #method(&self, #(#args: #types,)* #amount_arg)
+                -> Result<::iotex_antenna_rust::rpc::PendingReceipt<'_, C>, #error>
+            {
+                self.contract.execute(#name, &[#(#tokens),*], #amount)
+            }
+        }
+    }
+}
+
+// event_kind is the decoded type of an event input: dynamic indexed inputs are
+// only available as the hash of their value
+fn event_kind(p: &Param) -> ParamType {
+    match p.kind {
+        ParamType::String
+        | ParamType::Bytes
+        | ParamType::Array(_)
+        | ParamType::FixedArray(_, _)
+            if p.indexed =>
+        {
+            ParamType::FixedBytes(32)
+        }
+        _ => p.kind.clone(),
+    }
+}
+
+fn event_fields(event: &Event) -> Vec<Ident> {
+    param_idents(&event.inputs, "field")
+}
+
+fn event_struct(contract: &Ident, event: &Event) -> TokenStream {
+    let name = format_ident!("{}{}", contract, event.name);
+    let fields = event_fields(event);
+    let types = event.inputs.iter().map(|p| rust_type(&event_kind(p)));
+    quote! {
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct #name {
+            #(pub #fields: #types,)*
+        }
+    }
+}
+
+fn event_decoder(contract: &Ident, event: &Event) -> TokenStream {
+    let name = format_ident!("{}{}", contract, event.name);
+    let method = format_ident!("{}_events", snake_case(&event.name));
+    let event_name = &event.name;
+    let fields = event_fields(event);
+    let values = event.inputs.iter().map(|p| {
+        let value = from_token(&event_kind(p), quote!(t));
+        quote! {
+            match params.next() {
+                Some(t) => #value,
+                None => return Err(Self::invalid()),
+            }
+        }
+    });
+    quote! {
+        pub fn #method(
+            &self,
+            receipt: &::iotex_antenna_rust::action::Receipt,
+        ) -> Result<Vec<#name>, ::iotex_antenna_rust::contract::ContractError> {
+            let mut events = Vec::new();
+            for event in self.contract.decode_logs(receipt)? {
+                if event.name != #event_name {
+                    continue;
+                }
+                let mut params = event.params.into_iter().map(|p| p.value);
+                events.push(#name {
+                    #(#fields: #values,)*
+                });
+            }
+            Ok(events)
+        }
+    }
+}
+
+#[test]
+fn test_snake_case() {
+    let tests = [
+        ("balanceOf", "balance_of"),
+        ("totalSupply", "total_supply"),
+        ("tokenURI", "token_uri"),
+        ("DOMAIN_SEPARATOR", "domain_separator"),
+        ("safeTransferFrom", "safe_transfer_from"),
+        ("ERC20Token", "erc20_token"),
+        ("uri", "uri"),
+    ];
+    for test in tests.iter() {
+        assert_eq!(snake_case(test.0), test.1);
+    }
+    assert_eq!(ident("type", String::new()).to_string(), "type_");
+    assert_eq!(ident("", String::from("arg0")).to_string(), "arg0");
+    assert_eq!(ident("_owner", String::new()).to_string(), "owner");
+    assert_eq!(ident("new", String::new()).to_string(), "new_");
+
+    let param = |name: &str| Param {
+        name: String::from(name),
+        kind: ParamType::Bool,
+        indexed: false,
+    };
+    let names: Vec<String> = param_idents(
+        &[param("to"), param("_to"), param("arg2"), param("")],
+        "arg",
+    )
+    .iter()
+    .map(|i| i.to_string())
+    .collect();
+    assert_eq!(names, ["to", "to_1", "arg2", "arg3"]);
+    let names: Vec<String> = param_idents(&[param("arg1"), param("")], "arg")
+        .iter()
+        .map(|i| i.to_string())
+        .collect();
+    assert_eq!(names, ["arg1", "arg1_1"]);
+}
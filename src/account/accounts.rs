@@ -1,46 +1,117 @@
+use super::address_book::{Directory, Metadata, Resolver, ToAddress};
+use super::watch_only::WatchOnly;
 use super::*;
-use crate::address::{v1::AddrV1, Address};
+use crate::address::v1::AddrV1;
 use std::collections::HashMap;
 
 pub struct Accounts {
-    accounts: HashMap<String, account>,
+    accounts: HashMap<AddrV1, Box<dyn Signer>>,
+    directory: Directory,
 }
 
-impl accounts {
+impl Accounts {
     // new_accounts return Accounts instance
-    pub fn new_accounts() -> accounts {
+    pub fn new_accounts() -> Accounts {
         let accounts = HashMap::new();
-        accounts { accounts: accounts }
+        Accounts {
+            accounts,
+            directory: Directory::new(),
+        }
     }
     // create new account
     pub fn create(&mut self) -> Result<account, AccountError> {
-        match account::new_account() {
-            Ok(acc) => {
-                self.accounts.insert(acc.address().string(), acc);
-                Ok(acc)
-            }
-            Err(e) => Err(e),
-        }
+        let acc = account::new_account()?;
+        self.accounts.insert(acc.address(), Box::new(acc.clone()));
+        self.directory.insert(acc.address());
+        Ok(acc)
     }
-    // get_account by address
-    pub fn get_account(&self, addr: AddrV1) -> Option<account> {
-        match self.accounts.get(&addr.string()) {
-            Some(acc) => Some(*acc),
-            None => None,
-        }
+    // get_account by address, alias or encoded address
+    pub fn get_account<A: ToAddress>(&self, addr: A) -> Option<&dyn Signer> {
+        let addr = addr.to_address(self).ok()?;
+        self.accounts.get(&addr).map(|s| s.as_ref())
+    }
+    // get_account_by_name by alias or encoded address
+    pub fn get_account_by_name(&self, name: &str) -> Option<&dyn Signer> {
+        self.get_account(name)
     }
     // add_account add an account
     pub fn add_account(&mut self, acc: account) -> Option<AccountError> {
-        match self.get_account(acc.address()) {
-            Some(_) => return Some(AccountError::AccountExist(acc)),
+        self.add_signer(Box::new(acc))
+    }
+    // add_signer adds a signer, e.g. one backed by a key custody system
+    pub fn add_signer(&mut self, signer: Box<dyn Signer>) -> Option<AccountError> {
+        let addr = signer.address();
+        match self.get_account(addr) {
+            Some(_) => Some(AccountError::AccountExist(addr)),
             None => {
-                self.accounts.insert(acc.address().string(), acc);
+                self.accounts.insert(addr, signer);
+                self.directory.insert(addr);
                 None
             }
         }
     }
-    // remove_account removes an account
-    pub fn remove_account(&mut self, addr: AddrV1) {
-        self.accounts.remove(&addr.string());
+    // add_watch_only adds an account whose key is not held
+    pub fn add_watch_only(&mut self, acc: WatchOnly) -> Option<AccountError> {
+        self.add_signer(Box::new(acc))
+    }
+    // verify verifies that sig is a signature of data by the account addr; the
+    // signer of watch-only accounts, whose public key may not be known, is
+    // recovered from sig
+    pub fn verify<A: ToAddress>(
+        &self,
+        addr: A,
+        data: &[u8],
+        sig: &[u8],
+    ) -> Result<bool, AccountError> {
+        let addr = addr.to_address(self)?;
+        let signer = match self.get_account(addr) {
+            Some(r) => r,
+            None => return Err(AccountError::AccountNotExist(addr)),
+        };
+        if signer.watch_only() {
+            return Ok(recover_address(data, sig)? == addr);
+        }
+        match key::verify_sig(data, sig, signer.public_key()) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(AccountError::CryptoError(e)),
+        }
+    }
+    // remove_account removes an account along with its alias and labels
+    pub fn remove_account<A: ToAddress>(&mut self, addr: A) {
+        let addr = match addr.to_address(self) {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        self.accounts.remove(&addr);
+        self.directory.remove(addr);
+    }
+    // metadata returns the alias and labels of an account
+    pub fn metadata<A: ToAddress>(&self, addr: A) -> Option<&Metadata> {
+        self.directory.get(addr.to_address(self).ok()?)
+    }
+    // set_alias sets or, with None, clears the alias of an account
+    pub fn set_alias<A: ToAddress>(
+        &mut self,
+        addr: A,
+        alias: Option<&str>,
+    ) -> Result<(), AccountError> {
+        let addr = addr.to_address(self)?;
+        self.directory.set_alias(addr, alias)
+    }
+    // set_label sets or, with None, clears a label of an account
+    pub fn set_label<A: ToAddress>(
+        &mut self,
+        addr: A,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), AccountError> {
+        let addr = addr.to_address(self)?;
+        self.directory.set_label(addr, key, value)
+    }
+}
+
+impl Resolver for Accounts {
+    fn resolve(&self, name: &str) -> Option<AddrV1> {
+        self.directory.resolve(name)
     }
 }
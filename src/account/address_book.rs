@@ -0,0 +1,365 @@
+// Aliases and labels for addresses, and an address book of counterparties
+// that can be resolved by alias. Names are turned into addresses by a Resolver,
+// such as Accounts, Wallet or AddressBook; APIs taking a ToAddress, e.g. those
+// of accounts, contracts and tokens, accept an AddrV1, an alias or an encoded
+// address alike.
+use super::wallet::{write_atomic, WalletError};
+use super::*;
+use crate::address::{v1::AddrV1, Address};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+// Resolver turns an alias or an encoded address into an address
+pub trait Resolver {
+    fn resolve(&self, name: &str) -> Option<AddrV1>;
+}
+
+// resolvers are tried in order
+impl Resolver for [&dyn Resolver] {
+    fn resolve(&self, name: &str) -> Option<AddrV1> {
+        self.iter().find_map(|r| r.resolve(name))
+    }
+}
+
+// ToAddress is an address, or a name that a Resolver turns into one
+pub trait ToAddress {
+    fn to_address(&self, resolver: &dyn Resolver) -> Result<AddrV1, AccountError>;
+}
+
+impl ToAddress for AddrV1 {
+    fn to_address(&self, _resolver: &dyn Resolver) -> Result<AddrV1, AccountError> {
+        Ok(*self)
+    }
+}
+
+impl ToAddress for str {
+    fn to_address(&self, resolver: &dyn Resolver) -> Result<AddrV1, AccountError> {
+        match resolver.resolve(self) {
+            Some(r) => Ok(r),
+            None => Err(AccountError::UnknownName(String::from(self))),
+        }
+    }
+}
+
+impl ToAddress for String {
+    fn to_address(&self, resolver: &dyn Resolver) -> Result<AddrV1, AccountError> {
+        self.as_str().to_address(resolver)
+    }
+}
+
+impl<T: ToAddress + ?Sized> ToAddress for &T {
+    fn to_address(&self, resolver: &dyn Resolver) -> Result<AddrV1, AccountError> {
+        (**self).to_address(resolver)
+    }
+}
+
+impl<T: Resolver + ?Sized> Resolver for Arc<T> {
+    fn resolve(&self, name: &str) -> Option<AddrV1> {
+        (**self).resolve(name)
+    }
+}
+
+// Metadata is what is known of an address besides its key
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    pub alias: Option<String>,
+    pub labels: BTreeMap<String, String>,
+}
+
+// Directory holds the metadata of a set of addresses, with unique aliases
+#[derive(Clone, Debug, Default)]
+pub struct Directory {
+    entries: HashMap<AddrV1, Metadata>,
+    aliases: HashMap<String, AddrV1>,
+}
+
+impl Directory {
+    pub fn new() -> Directory {
+        Directory::default()
+    }
+    // insert adds addr without metadata; it returns false if addr is present
+    pub fn insert(&mut self, addr: AddrV1) -> bool {
+        if self.contains(addr) {
+            return false;
+        }
+        self.entries.insert(addr, Metadata::default());
+        true
+    }
+    // remove removes addr and its alias
+    pub fn remove(&mut self, addr: AddrV1) {
+        if let Some(m) = self.entries.remove(&addr) {
+            if let Some(alias) = m.alias {
+                self.aliases.remove(&alias);
+            }
+        }
+    }
+    pub fn contains(&self, addr: AddrV1) -> bool {
+        self.entries.contains_key(&addr)
+    }
+    pub fn get(&self, addr: AddrV1) -> Option<&Metadata> {
+        self.entries.get(&addr)
+    }
+    // set_alias sets or, with None, clears the alias of addr; aliases are
+    // unique and cannot themselves be addresses
+    pub fn set_alias(&mut self, addr: AddrV1, alias: Option<&str>) -> Result<(), AccountError> {
+        if !self.contains(addr) {
+            return Err(AccountError::AccountNotExist(addr));
+        }
+        if let Some(alias) = alias {
+            if alias.is_empty() || alias.trim() != alias || address::from_string(alias).is_ok() {
+                return Err(AccountError::InvalidAlias(String::from(alias)));
+            }
+            match self.aliases.get(alias) {
+                Some(a) if *a == addr => return Ok(()),
+                Some(_) => return Err(AccountError::AliasExist(String::from(alias))),
+                None => (),
+            }
+        }
+        let entry = self.entries.get_mut(&addr).unwrap();
+        if let Some(old) = entry.alias.take() {
+            self.aliases.remove(&old);
+        }
+        if let Some(alias) = alias {
+            entry.alias = Some(String::from(alias));
+            self.aliases.insert(String::from(alias), addr);
+        }
+        Ok(())
+    }
+    // set_label sets or, with None, clears the label key of addr
+    pub fn set_label(
+        &mut self,
+        addr: AddrV1,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), AccountError> {
+        let entry = match self.entries.get_mut(&addr) {
+            Some(r) => r,
+            None => return Err(AccountError::AccountNotExist(addr)),
+        };
+        match value {
+            Some(v) => entry.labels.insert(String::from(key), String::from(v)),
+            None => entry.labels.remove(key),
+        };
+        Ok(())
+    }
+    // to_json encodes the metadata of addrs, in that order; addresses are
+    // written as hex so that the file does not depend on the network prefix
+    pub(super) fn to_json(&self, addrs: &[AddrV1]) -> Value {
+        let entries: Vec<Value> = addrs
+            .iter()
+            .map(|addr| {
+                let mut entry = Map::new();
+                entry.insert(String::from("address"), json!(hex::encode(addr.bytes())));
+                if let Some(m) = self.get(*addr) {
+                    if let Some(alias) = &m.alias {
+                        entry.insert(String::from("alias"), json!(alias));
+                    }
+                    if !m.labels.is_empty() {
+                        entry.insert(String::from("labels"), json!(m.labels));
+                    }
+                }
+                Value::Object(entry)
+            })
+            .collect();
+        Value::Array(entries)
+    }
+    // from_json decodes entries written by to_json, or plain addresses, and
+    // returns the addresses in order; addresses can be hex or encoded
+    pub(super) fn from_json(entries: &Value) -> Result<(Directory, Vec<AddrV1>), String> {
+        let entries = match entries.as_array() {
+            Some(r) => r,
+            None => return Err(String::from("missing entries")),
+        };
+        let mut directory = Directory::new();
+        let mut addrs = Vec::new();
+        for entry in entries.iter() {
+            let encoded = match entry {
+                Value::String(s) => Some(s.as_str()),
+                _ => entry["address"].as_str(),
+            };
+            let addr = match encoded.map(parse_address) {
+                Some(Ok(r)) => r,
+                _ => return Err(entry.to_string()),
+            };
+            if !directory.insert(addr) {
+                return Err(format!("duplicate address {}", addr.string()));
+            }
+            addrs.push(addr);
+            let alias = match &entry["alias"] {
+                Value::Null => None,
+                Value::String(s) => Some(s.as_str()),
+                _ => return Err(format!("invalid alias of {}", addr.string())),
+            };
+            if let Err(e) = directory.set_alias(addr, alias) {
+                return Err(e.to_string());
+            }
+            let labels = match &entry["labels"] {
+                Value::Null => continue,
+                Value::Object(r) => r,
+                _ => return Err(format!("invalid labels of {}", addr.string())),
+            };
+            for (k, v) in labels.iter() {
+                let v = match v.as_str() {
+                    Some(r) => r,
+                    None => return Err(format!("invalid label {} of {}", k, addr.string())),
+                };
+                if let Err(e) = directory.set_label(addr, k, Some(v)) {
+                    return Err(e.to_string());
+                }
+            }
+        }
+        Ok((directory, addrs))
+    }
+}
+
+fn parse_address(s: &str) -> Result<AddrV1, address::AddrError> {
+    match hex::decode(s) {
+        Ok(bytes) => address::from_bytes(&bytes),
+        Err(_) => address::from_string(s),
+    }
+}
+
+impl Resolver for Directory {
+    fn resolve(&self, name: &str) -> Option<AddrV1> {
+        match self.aliases.get(name) {
+            Some(r) => Some(*r),
+            None => address::from_string(name).ok(),
+        }
+    }
+}
+
+// AddressBook names counterparties whose keys are not ours
+#[derive(Default)]
+pub struct AddressBook {
+    directory: Directory,
+    addrs: Vec<AddrV1>,
+}
+
+impl AddressBook {
+    pub fn new() -> AddressBook {
+        AddressBook::default()
+    }
+    // load reads an address book written by save
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<AddressBook, WalletError> {
+        let data: Value = match serde_json::from_slice(&fs::read(path)?) {
+            Ok(r) => r,
+            Err(e) => return Err(WalletError::InvalidIndex(e.to_string())),
+        };
+        match Directory::from_json(&data["contacts"]) {
+            Ok((directory, addrs)) => Ok(AddressBook { directory, addrs }),
+            Err(e) => Err(WalletError::InvalidIndex(e)),
+        }
+    }
+    // save writes the address book to path atomically
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), WalletError> {
+        let data = json!({ "contacts": self.directory.to_json(&self.addrs) });
+        write_atomic(path.as_ref(), data.to_string().as_bytes())?;
+        Ok(())
+    }
+    // add adds a counterparty with an optional alias
+    pub fn add(&mut self, addr: AddrV1, alias: Option<&str>) -> Result<(), AccountError> {
+        if !self.directory.insert(addr) {
+            return Err(AccountError::AccountExist(addr));
+        }
+        if let Err(e) = self.directory.set_alias(addr, alias) {
+            self.directory.remove(addr);
+            return Err(e);
+        }
+        self.addrs.push(addr);
+        Ok(())
+    }
+    pub fn remove(&mut self, addr: AddrV1) {
+        self.directory.remove(addr);
+        self.addrs.retain(|a| *a != addr);
+    }
+    // addresses returns the counterparties in the order they were added
+    pub fn addresses(&self) -> &[AddrV1] {
+        &self.addrs
+    }
+    pub fn get(&self, addr: AddrV1) -> Option<&Metadata> {
+        self.directory.get(addr)
+    }
+    pub fn set_alias(&mut self, addr: AddrV1, alias: Option<&str>) -> Result<(), AccountError> {
+        self.directory.set_alias(addr, alias)
+    }
+    pub fn set_label(
+        &mut self,
+        addr: AddrV1,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), AccountError> {
+        self.directory.set_label(addr, key, value)
+    }
+}
+
+impl Resolver for AddressBook {
+    fn resolve(&self, name: &str) -> Option<AddrV1> {
+        self.directory.resolve(name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_address_book() {
+        let alice = account::new_account().unwrap().address();
+        let bob = account::new_account().unwrap().address();
+        let mut book = AddressBook::new();
+        book.add(alice, Some("alice")).unwrap();
+        book.add(bob, None).unwrap();
+        assert_eq!(book.add(bob, None), Err(AccountError::AccountExist(bob)));
+        assert_eq!(
+            book.set_alias(bob, Some("alice")),
+            Err(AccountError::AliasExist(String::from("alice")))
+        );
+        assert_eq!(
+            book.set_alias(bob, Some(&alice.string())),
+            Err(AccountError::InvalidAlias(alice.string()))
+        );
+        book.set_alias(bob, Some("bob")).unwrap();
+        book.set_label(bob, "desk", Some("otc")).unwrap();
+        assert_eq!(book.resolve("alice"), Some(alice));
+        assert_eq!(book.resolve(&bob.string()), Some(bob));
+        assert_eq!(book.resolve("carol"), None);
+
+        let path = std::env::temp_dir().join(format!("iotex-book-{}.json", std::process::id()));
+        book.save(&path).unwrap();
+        let mut book = AddressBook::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(book.addresses(), &[alice, bob][..]);
+        assert_eq!(book.get(bob).unwrap().labels["desk"], "otc");
+        assert_eq!(book.resolve("bob"), Some(bob));
+
+        let entry = json!({ "address": hex::encode(alice.bytes()), "labels": { "desk": 1 } });
+        assert!(Directory::from_json(&json!([entry])).is_err());
+        let entry = json!({ "address": hex::encode(alice.bytes()), "alias": ["alice"] });
+        assert!(Directory::from_json(&json!([entry])).is_err());
+
+        book.remove(alice);
+        let mut accounts = accounts::Accounts::new_accounts();
+        let act = accounts.create().unwrap();
+        accounts.set_alias(act.address(), Some("alice")).unwrap();
+        let resolvers: [&dyn Resolver; 2] = [&accounts, &book];
+        assert_eq!(resolvers[..].resolve("alice"), Some(act.address()));
+        assert_eq!(resolvers[..].resolve("bob"), Some(bob));
+        assert_eq!(
+            accounts.get_account("alice").unwrap().address(),
+            act.address()
+        );
+        accounts.set_label("alice", "desk", Some("ops")).unwrap();
+        assert_eq!(
+            accounts.metadata(act.address()).unwrap().labels["desk"],
+            "ops"
+        );
+        assert_eq!("bob".to_address(&book), Ok(bob));
+        assert_eq!(
+            "carol".to_address(&book),
+            Err(AccountError::UnknownName(String::from("carol")))
+        );
+    }
+}
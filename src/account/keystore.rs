@@ -0,0 +1,332 @@
+// Encrypted keystores in the Web3 Secret Storage (version 3) format, with
+// scrypt key derivation and aes-128-ctr encryption, as ioPay and geth write.
+use super::*;
+use crate::address::Address;
+use aes::Aes128;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde_json::{json, Value};
+use std::{error, fmt};
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+// KdfParams are the scrypt cost parameters of new keystores
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl KdfParams {
+    // STANDARD is the cost geth uses by default
+    pub const STANDARD: KdfParams = KdfParams {
+        log_n: 18,
+        r: 8,
+        p: 1,
+    };
+    // LIGHT is the cost geth uses with --lightkdf
+    pub const LIGHT: KdfParams = KdfParams {
+        log_n: 12,
+        r: 8,
+        p: 6,
+    };
+}
+
+// limits of the parameters accepted from keystore files, so that a crafted
+// file cannot make scrypt allocate unbounded memory; 2^20 * 128 * 8 is 1 GiB
+const MAX_LOG_N: u8 = 20;
+const MAX_R: u32 = 8;
+const MAX_P: u32 = 16;
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams::STANDARD
+    }
+}
+
+// encrypt returns the keystore of key under password
+pub fn encrypt(
+    key: &key::PrivKey,
+    password: &[u8],
+    params: KdfParams,
+) -> Result<Value, KeystoreError> {
+    let mut rng = match OsRng::new() {
+        Ok(r) => r,
+        Err(e) => return Err(KeystoreError::RandomError(e.to_string())),
+    };
+    let mut salt = [0u8; 32];
+    let mut iv = [0u8; 16];
+    let mut id = [0u8; 16];
+    rng.fill_bytes(&mut salt);
+    rng.fill_bytes(&mut iv);
+    rng.fill_bytes(&mut id);
+    // random (version 4) UUID
+    id[6] = (id[6] & 0x0f) | 0x40;
+    id[8] = (id[8] & 0x3f) | 0x80;
+
+    let mut derived = derive(password, &salt, params)?;
+    let mut ciphertext = key.export_bytes();
+    apply_cipher(&derived, &iv, &mut ciphertext);
+    let mac = mac(&derived, &ciphertext);
+    key::wipe(&mut derived);
+
+    let acc = account::private_key_to_account(key.clone());
+    Ok(json!({
+        "version": 3,
+        "id": format!(
+            "{}-{}-{}-{}-{}",
+            hex::encode(&id[..4]),
+            hex::encode(&id[4..6]),
+            hex::encode(&id[6..8]),
+            hex::encode(&id[8..10]),
+            hex::encode(&id[10..])
+        ),
+        "address": hex::encode(acc.address().bytes()),
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": hex::encode(iv) },
+            "ciphertext": hex::encode(ciphertext),
+            "kdf": "scrypt",
+            "kdfparams": {
+                "dklen": 32,
+                "n": 1u64 << params.log_n,
+                "r": params.r,
+                "p": params.p,
+                "salt": hex::encode(salt),
+            },
+            "mac": hex::encode(mac.0),
+        },
+    }))
+}
+
+// decrypt returns the key of keystore, failing with WrongPassword if the MAC
+// does not match
+pub fn decrypt(keystore: &Value, password: &[u8]) -> Result<key::PrivKey, KeystoreError> {
+    if keystore["version"].as_u64() != Some(3) {
+        return Err(invalid("version"));
+    }
+    let crypto = &keystore["crypto"];
+    if crypto["cipher"].as_str() != Some("aes-128-ctr") {
+        return Err(invalid("cipher"));
+    }
+    if crypto["kdf"].as_str() != Some("scrypt") {
+        return Err(invalid("kdf"));
+    }
+    let kdf = &crypto["kdfparams"];
+    if kdf["dklen"].as_u64() != Some(32) {
+        return Err(invalid("dklen"));
+    }
+    let n = kdf["n"].as_u64().unwrap_or(0);
+    if !n.is_power_of_two() || n < 2 || n.trailing_zeros() > u32::from(MAX_LOG_N) {
+        return Err(invalid("n"));
+    }
+    let params = KdfParams {
+        log_n: n.trailing_zeros() as u8,
+        r: uint_field(kdf, "r", MAX_R)?,
+        p: uint_field(kdf, "p", MAX_P)?,
+    };
+    let salt = hex_field(kdf, "salt")?;
+    let iv = hex_field(&crypto["cipherparams"], "iv")?;
+    if iv.len() != 16 {
+        return Err(invalid("iv"));
+    }
+    let mut ciphertext = hex_field(crypto, "ciphertext")?;
+    if ciphertext.len() != 32 {
+        return Err(invalid("ciphertext"));
+    }
+
+    let mut derived = derive(password, &salt, params)?;
+    if !constant_time_eq(&hex_field(crypto, "mac")?, &mac(&derived, &ciphertext).0) {
+        key::wipe(&mut derived);
+        return Err(KeystoreError::WrongPassword);
+    }
+    apply_cipher(&derived, &iv, &mut ciphertext);
+    key::wipe(&mut derived);
+    let mut raw = [0u8; 32];
+    raw.copy_from_slice(&ciphertext);
+    key::wipe(&mut ciphertext);
+    let key = key::PrivKey::from_slice(&raw);
+    key::wipe(&mut raw);
+    let key = key?;
+
+    if let Some(addr) = keystore["address"].as_str() {
+        let acc = account::private_key_to_account(key.clone());
+        if !addr
+            .trim_start_matches("0x")
+            .eq_ignore_ascii_case(&hex::encode(acc.address().bytes()))
+        {
+            return Err(invalid("address"));
+        }
+    }
+    Ok(key)
+}
+
+fn derive(password: &[u8], salt: &[u8], params: KdfParams) -> Result<[u8; 32], KeystoreError> {
+    let scrypt_params = match scrypt::Params::new(params.log_n, params.r, params.p, 32) {
+        Ok(r) => r,
+        Err(_) => return Err(invalid("kdfparams")),
+    };
+    let mut derived = [0u8; 32];
+    match scrypt::scrypt(password, salt, &scrypt_params, &mut derived) {
+        Ok(_) => Ok(derived),
+        Err(_) => Err(invalid("dklen")),
+    }
+}
+
+fn apply_cipher(derived: &[u8; 32], iv: &[u8], data: &mut [u8]) {
+    let mut cipher = Aes128Ctr::new(derived[..16].into(), iv.into());
+    cipher.apply_keystream(data);
+}
+
+fn mac(derived: &[u8; 32], ciphertext: &[u8]) -> hash::Hash256b {
+    let mut data = derived[16..].to_vec();
+    data.extend_from_slice(ciphertext);
+    let mac = hash::hash256b(&data);
+    key::wipe(&mut data);
+    mac
+}
+
+// constant_time_eq compares without returning early, so that the time taken
+// does not tell how many bytes of the MAC matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn hex_field(value: &Value, name: &str) -> Result<Vec<u8>, KeystoreError> {
+    match value[name].as_str().map(hex::decode) {
+        Some(Ok(r)) => Ok(r),
+        _ => Err(invalid(name)),
+    }
+}
+
+fn uint_field(value: &Value, name: &str, max: u32) -> Result<u32, KeystoreError> {
+    match value[name].as_u64() {
+        Some(r) if r >= 1 && r <= u64::from(max) => Ok(r as u32),
+        _ => Err(invalid(name)),
+    }
+}
+
+fn invalid(field: &str) -> KeystoreError {
+    KeystoreError::InvalidKeystore(String::from(field))
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum KeystoreError {
+    // InvalidKeystore names the field that is missing, malformed or unsupported
+    InvalidKeystore(String),
+    WrongPassword,
+    RandomError(String),
+    CryptoError(crypto::Error),
+}
+
+impl From<crypto::Error> for KeystoreError {
+    fn from(e: crypto::Error) -> Self {
+        KeystoreError::CryptoError(e)
+    }
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeystoreError::InvalidKeystore(e) => write!(f, "invalid keystore {}", e),
+            KeystoreError::WrongPassword => write!(f, "wrong password"),
+            KeystoreError::RandomError(e) => write!(f, "random source error, {}", e),
+            KeystoreError::CryptoError(e) => write!(f, "crypto error, {}", e),
+        }
+    }
+}
+
+impl error::Error for KeystoreError {
+    fn description(&self) -> &str {
+        match *self {
+            KeystoreError::InvalidKeystore(_) => "invalid keystore",
+            KeystoreError::WrongPassword => "wrong password",
+            KeystoreError::RandomError(_) => "random source error",
+            KeystoreError::CryptoError(_) => "crypto error",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "0806c458b262edd333a191e92f561aff338211ee3e18ab315a074a2d82aa343f";
+
+    #[test]
+    fn test_keystore() {
+        let params = KdfParams {
+            log_n: 10,
+            r: 8,
+            p: 1,
+        };
+        let key = key::hex_string_to_private(String::from(PRIVATE_KEY)).unwrap();
+        let keystore = encrypt(&key, b"testpassword", params).unwrap();
+        assert_eq!(keystore["crypto"]["kdfparams"]["n"], 1024);
+        assert_eq!(keystore["id"].as_str().unwrap().len(), 36);
+        assert_eq!(decrypt(&keystore, b"testpassword").unwrap(), key);
+        assert_eq!(
+            decrypt(&keystore, b"wrongpassword"),
+            Err(KeystoreError::WrongPassword)
+        );
+
+        let mut other = keystore.clone();
+        other["address"] = json!("0000000000000000000000000000000000000000");
+        assert_eq!(
+            decrypt(&other, b"testpassword"),
+            Err(KeystoreError::InvalidKeystore(String::from("address")))
+        );
+        let mut other = keystore.clone();
+        other["crypto"]["kdf"] = json!("pbkdf2");
+        assert_eq!(
+            decrypt(&other, b"testpassword"),
+            Err(KeystoreError::InvalidKeystore(String::from("kdf")))
+        );
+        // costs that would exhaust memory are refused before running scrypt
+        let mut other = keystore.clone();
+        other["crypto"]["kdfparams"]["n"] = json!(1u64 << 63);
+        assert_eq!(
+            decrypt(&other, b"testpassword"),
+            Err(KeystoreError::InvalidKeystore(String::from("n")))
+        );
+        for field in ["r", "p"].iter() {
+            let mut other = keystore.clone();
+            other["crypto"]["kdfparams"][*field] = json!(u32::MAX);
+            assert_eq!(
+                decrypt(&other, b"testpassword"),
+                Err(KeystoreError::InvalidKeystore(String::from(*field)))
+            );
+        }
+    }
+
+    #[test]
+    fn test_keystore_vector() {
+        // the keystore of the web3.js accounts.encrypt documentation
+        let keystore = json!({
+            "version": 3,
+            "id": "04e9bcbb-96fa-497b-94d1-14df4cd20af6",
+            "address": "2c7536e3605d9c16a7a3d7b1898e529396a65c23",
+            "crypto": {
+                "ciphertext": "a1c25da3ecde4e6a24f3697251dd15d6208520efc84ad97397e906e6df24d251",
+                "cipherparams": { "iv": "2885df2b63f7ef247d753c82fa20038a" },
+                "cipher": "aes-128-ctr",
+                "kdf": "scrypt",
+                "kdfparams": {
+                    "dklen": 32,
+                    "salt": "4531b3c174cc3ff32a6a7a85d6761b410db674807b2d216d022318ceee50be10",
+                    "n": 262144,
+                    "r": 8,
+                    "p": 1
+                },
+                "mac": "b8b010fff37f9ae5559a352a185e86f9b9c1d7f7a9f1bd4e82a5dd35468fc7f6"
+            }
+        });
+        let expected = key::hex_string_to_private(String::from(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+        ))
+        .unwrap();
+        assert_eq!(decrypt(&keystore, b"test!").unwrap(), expected);
+    }
+}
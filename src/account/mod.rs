@@ -1,11 +1,25 @@
 use super::{
     address,
-    crypto::{self, key},
+    crypto::{self, hash, key},
+    rpc,
 };
+use std::{error, fmt, thread};
 
 pub mod accounts;
+pub mod address_book;
+pub mod keystore;
+pub mod remote;
+pub mod shared;
+pub mod signer;
+pub mod typed_data;
+pub mod wallet;
+pub mod watch_only;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+pub use signer::{Signer, SignerError};
+pub use watch_only::WatchOnly;
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct account {
     private: key::PrivKey,
     address: address::v1::AddrV1,
@@ -17,7 +31,7 @@ impl account {
         let new_key = key::PrivKey::new();
         let hash = match key::public_key_hash(new_key.public_key()) {
             Ok(r) => r.0,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
         let addr = match address::from_bytes(&hash) {
             Ok(r) => r,
@@ -39,11 +53,11 @@ impl account {
     pub fn private_key_to_account(key: key::PrivKey) -> account {
         let hash = match key::public_key_hash(key.public_key()) {
             Ok(r) => r,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
         let addr = match address::from_bytes(&hash.0) {
             Ok(r) => r,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{}", e),
         };
         account {
             private: key,
@@ -55,8 +69,8 @@ impl account {
         self.address
     }
     // private_key return the embedded private key
-    pub fn private_key(&self) -> key::PrivKey {
-        self.private
+    pub fn private_key(&self) -> &key::PrivKey {
+        &self.private
     }
     // public_key returns the embedded public key string
     pub fn public_key(&self) -> String {
@@ -73,19 +87,165 @@ impl account {
             Err(e) => Err(AccountError::CryptoError(e)),
         }
     }
+    // personal_sign signs message the EIP-191 way, like ioPay and MetaMask do;
+    // the recovery ID of the signature is 27 or 28
+    pub fn personal_sign(&self, message: &[u8]) -> [u8; 65] {
+        let mut sig = self
+            .private
+            .sign_digest(&hash::personal_message_hash(message));
+        sig[64] += 27;
+        sig
+    }
+    // sign_typed_data signs data the EIP-712 way; the recovery ID of the
+    // signature is 27 or 28
+    pub fn sign_typed_data(
+        &self,
+        data: &typed_data::TypedData,
+    ) -> Result<[u8; 65], typed_data::TypedDataError> {
+        data.sign(&self.private)
+    }
+    // verify_personal verifies that sig is a personal_sign of message by this account
+    pub fn verify_personal(&self, message: &[u8], sig: &[u8]) -> Result<bool, AccountError> {
+        Ok(recover_personal(message, sig)? == self.address)
+    }
+}
+
+// recover_personal returns the address that personal-signed message; the
+// recovery ID of sig may be 0/1 or 27/28
+pub fn recover_personal(message: &[u8], sig: &[u8]) -> Result<address::v1::AddrV1, AccountError> {
+    recover_signer(&hash::personal_message_hash(message), sig)
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+// recover_address returns the address whose key signed data, as PrivKey::sign
+// and action signatures do
+pub fn recover_address(data: &[u8], sig: &[u8]) -> Result<address::v1::AddrV1, AccountError> {
+    recover_signer(&hash::hash256b(data), sig)
+}
+
+// recover_addresses recovers the signers of many (data, sig) pairs in parallel;
+// results are in the order of items
+pub fn recover_addresses<D, S>(items: &[(D, S)]) -> Vec<Result<address::v1::AddrV1, AccountError>>
+where
+    D: AsRef<[u8]> + Sync,
+    S: AsRef<[u8]> + Sync,
+{
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = ((items.len() + threads - 1) / threads).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(data, sig)| recover_address(data.as_ref(), sig.as_ref()))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("recovery thread panicked"))
+            .collect()
+    })
+}
+
+// recover_signer returns the address whose key signed digest; the recovery ID
+// of sig may be 0/1 or 27/28
+fn recover_signer(
+    digest: &hash::Hash256b,
+    sig: &[u8],
+) -> Result<address::v1::AddrV1, AccountError> {
+    if sig.len() != 65 {
+        return Err(AccountError::CryptoError(crypto::Error::InvalidSignature));
+    }
+    let mut signature = [0u8; 65];
+    signature.copy_from_slice(sig);
+    if signature[64] >= 27 {
+        signature[64] -= 27;
+    }
+    let public_key = match key::recover_digest(digest, &signature) {
+        Ok(r) => r,
+        Err(e) => return Err(AccountError::CryptoError(e)),
+    };
+    let hash = match key::public_key_hash(hex::encode(&public_key[..])) {
+        Ok(r) => r,
+        Err(e) => return Err(AccountError::CryptoError(e)),
+    };
+    match address::from_bytes(&hash.0) {
+        Ok(r) => Ok(r),
+        Err(e) => Err(AccountError::AddressError(e)),
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub enum AccountError {
     AddressError(address::AddrError),
     CryptoError(crypto::Error),
-    AccountExist(account),
+    AccountExist(address::v1::AddrV1),
     AccountNotExist(address::v1::AddrV1),
+    AliasExist(String),
+    InvalidAlias(String),
+    UnknownName(String),
+    SignerError(SignerError),
+    RpcError(rpc::RpcError),
+    // NonceGap is a failed signature with a nonce that could not be given back
+    // because later nonces were already handed out
+    NonceGap(u64, SignerError),
+}
+
+impl From<SignerError> for AccountError {
+    fn from(e: SignerError) -> Self {
+        AccountError::SignerError(e)
+    }
+}
+
+impl From<rpc::RpcError> for AccountError {
+    fn from(e: rpc::RpcError) -> Self {
+        AccountError::RpcError(e)
+    }
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccountError::AddressError(e) => write!(f, "address error, {}", e),
+            AccountError::CryptoError(e) => write!(f, "crypto error, {}", e),
+            AccountError::AccountExist(a) => write!(f, "account {} already exists", a),
+            AccountError::AccountNotExist(a) => write!(f, "account {} does not exist", a),
+            AccountError::AliasExist(a) => write!(f, "alias {} already exists", a),
+            AccountError::InvalidAlias(a) => write!(f, "invalid alias {}", a),
+            AccountError::UnknownName(a) => write!(f, "unknown name {}", a),
+            AccountError::SignerError(e) => write!(f, "signer error, {}", e),
+            AccountError::RpcError(e) => write!(f, "rpc error, {}", e),
+            AccountError::NonceGap(n, e) => write!(f, "nonce {} left unused, {}", n, e),
+        }
+    }
+}
+
+impl error::Error for AccountError {
+    fn description(&self) -> &str {
+        match *self {
+            AccountError::AddressError(_) => "address error",
+            AccountError::CryptoError(_) => "crypto error",
+            AccountError::AccountExist(_) => "account already exists",
+            AccountError::AccountNotExist(_) => "account does not exist",
+            AccountError::AliasExist(_) => "alias already exists",
+            AccountError::InvalidAlias(_) => "invalid alias",
+            AccountError::UnknownName(_) => "unknown name",
+            AccountError::SignerError(_) => "signer error",
+            AccountError::RpcError(_) => "rpc error",
+            AccountError::NonceGap(_, _) => "nonce left unused",
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{account, accounts::accounts, address::Address, crypto::hash};
+    use super::{
+        account, accounts::Accounts, address::Address, crypto::hash, recover_address,
+        recover_addresses, recover_personal, AccountError,
+    };
     const TEXT: &str = "IoTeX is the auto-scalable and privacy-centric blockchain.";
     const ADDR: &str = "io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j";
     const PUBLIC_KEY: &str = "044e18306ae9ef4ec9d07bf6e705442d4d1a75e6cdf750330ca2d880f2cc54607c9c33deb9eae9c06e06e04fe9ce3d43962cc67d5aa34fbeb71270d4bad3d648d9";
@@ -103,51 +263,106 @@ mod test {
 
         let act = match account::hex_string_to_account(String::from(PRIVATE_KEY)) {
             Ok(r) => r,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{:?}", e),
         };
         assert_eq!(act.address().string(), ADDR);
         assert_eq!(act.public_key(), PUBLIC_KEY);
 
-        let act1 = account::private_key_to_account(act.private_key());
+        let act1 = account::private_key_to_account(act.private_key().clone());
         let sig = act1.sign(TEXT.as_bytes());
         println!("{}", hex::encode(h.0));
         assert_eq!(
             hex::encode(&sig[..]),
             String::from("482da72c8faa48ee1ac2cf9a5f9ecd42ee3258be5ddd8d6b496c7171dc7bfe8e75e5d16e7129c88d99a21a912e5c082fa1baab6ba87d2688ebd7d27bb1ab090701")
         );
-        assert_eq!(act1.verify(TEXT.as_bytes(), &sig).unwrap(), true);
+        assert!(act1.verify(TEXT.as_bytes(), &sig).unwrap());
 
         let act2 = account::new_account().unwrap();
         let act3 = account::new_account().unwrap();
         assert_ne!(act2.private_key(), act3.private_key())
     }
     #[test]
+    fn test_personal_sign() {
+        // vector from the web3.js documentation of eth.accounts.sign
+        let act = account::hex_string_to_account(String::from(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+        ))
+        .unwrap();
+        let sig = act.personal_sign(b"Some data");
+        assert_eq!(
+            hex::encode(&sig[..]),
+            "b91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c"
+        );
+        assert_eq!(
+            hex::encode(act.address().bytes()),
+            "2c7536e3605d9c16a7a3d7b1898e529396a65c23"
+        );
+        assert_eq!(recover_personal(b"Some data", &sig).unwrap(), act.address());
+        assert!(act.verify_personal(b"Some data", &sig).unwrap());
+        assert!(!act.verify_personal(b"Other data", &sig).unwrap());
+
+        let mut raw = sig;
+        raw[64] -= 27;
+        assert!(act.verify_personal(b"Some data", &raw).unwrap());
+        assert!(recover_personal(b"Some data", &sig[..64]).is_err());
+    }
+    #[test]
+    fn test_recover_address() {
+        let acts: Vec<account> = (0..5).map(|_| account::new_account().unwrap()).collect();
+        let items: Vec<(Vec<u8>, [u8; 65])> = acts
+            .iter()
+            .map(|a| {
+                let data = a.public_key().into_bytes();
+                let sig = a.sign(&data);
+                (data, sig)
+            })
+            .collect();
+        assert_eq!(
+            recover_address(&items[0].0, &items[0].1).unwrap(),
+            acts[0].address()
+        );
+        let recovered = recover_addresses(&items);
+        assert_eq!(recovered.len(), acts.len());
+        for (r, a) in recovered.iter().zip(acts.iter()) {
+            assert_eq!(*r, Ok(a.address()));
+        }
+        assert!(recover_addresses(&[(b"data", &[0u8; 10][..])])[0].is_err());
+        assert!(recover_addresses::<&[u8], &[u8]>(&[]).is_empty());
+    }
+    #[test]
     fn test_accounts() {
-        let mut acts = accounts::new_accounts();
+        let mut acts = Accounts::new_accounts();
         let act1 = acts.create().unwrap();
         let act2 = acts.create().unwrap();
         assert_ne!(act1, act2);
 
         match acts.get_account(act1.address) {
-            Some(acc) => assert_eq!(acc.private_key(), act1.private_key()),
+            Some(acc) => assert_eq!(acc.public_key(), act1.public_key()),
             None => panic!("failed to get account just created"),
         }
 
         let act3 = match account::hex_string_to_account(String::from(PRIVATE_KEY)) {
             Ok(r) => r,
-            Err(e) => panic!(e),
+            Err(e) => panic!("{:?}", e),
         };
-        match acts.add_account(act3) {
-            Some(e) => panic!(e),
-            None => (),
+        if let Some(e) = acts.add_account(act3.clone()) {
+            panic!("{:?}", e)
         }
         let act4 = acts.get_account(act3.address()).unwrap();
-        assert_eq!(act3, act4);
+        assert_eq!(act4.address(), act3.address());
+        let digest = hash::hash256b(TEXT.as_bytes());
+        assert_eq!(
+            act4.sign_digest(&digest).unwrap()[..],
+            act3.sign(TEXT.as_bytes())[..]
+        );
+        assert_eq!(
+            acts.add_account(act3.clone()),
+            Some(AccountError::AccountExist(act3.address()))
+        );
 
-        acts.remove_account(act4.address());
-        match acts.get_account(act3.address()) {
-            Some(_) => panic!("account should have been removed"),
-            None => (),
+        acts.remove_account(act3.address());
+        if acts.get_account(act3.address()).is_some() {
+            panic!("account should have been removed")
         }
     }
 }
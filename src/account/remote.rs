@@ -0,0 +1,457 @@
+// Remote signing over a Unix socket or HTTP.
+//
+// Protocol: every request is a JSON object with a "method" field, answered by
+// a JSON object which carries an "error" string if the request failed.
+//
+//   {"method":"public_key"}
+//     -> {"public_key":"<hex uncompressed public key>"}
+//   {"method":"sign_digest","digest":"<hex 32 bytes>"}
+//     -> {"signature":"<hex 65 bytes, recovery ID 0 or 1>"}
+//
+// On a Unix socket, a connection carries one request line and one response
+// line, both newline terminated. Over HTTP, the request is the body of a POST
+// to the endpoint and the response the body of the reply.
+use super::*;
+use crate::address::v1::AddrV1;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+// DEFAULT_TIMEOUT bounds each exchange with the remote signer
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// MAX_MESSAGE_SIZE bounds the requests and responses read from the other side
+const MAX_MESSAGE_SIZE: u64 = 64 * 1024;
+
+// Endpoint is where a remote signer listens
+#[derive(Clone, Debug, PartialEq)]
+pub enum Endpoint {
+    #[cfg(unix)]
+    Unix(PathBuf),
+    // Http is a host:port and the path requests are posted to
+    Http {
+        host: String,
+        path: String,
+    },
+}
+
+// RemoteSigner signs through a signing daemon holding the key
+pub struct RemoteSigner {
+    endpoint: Endpoint,
+    timeout: Duration,
+    public_key: String,
+    address: AddrV1,
+}
+
+impl RemoteSigner {
+    // unix connects to a signing daemon listening on the Unix socket at path
+    #[cfg(unix)]
+    pub fn unix<P: AsRef<Path>>(path: P) -> Result<RemoteSigner, SignerError> {
+        RemoteSigner::connect(Endpoint::Unix(path.as_ref().to_path_buf()))
+    }
+    // http connects to a signing daemon at an http://host:port/path URL
+    pub fn http(url: &str) -> Result<RemoteSigner, SignerError> {
+        let rest = match url.strip_prefix("http://") {
+            Some(r) => r,
+            None => return Err(unavailable("only http:// endpoints are supported")),
+        };
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        RemoteSigner::connect(Endpoint::Http {
+            host: String::from(host),
+            path: String::from(path),
+        })
+    }
+    // connect fetches the public key served at endpoint
+    pub fn connect(endpoint: Endpoint) -> Result<RemoteSigner, SignerError> {
+        RemoteSigner::connect_timeout(endpoint, DEFAULT_TIMEOUT)
+    }
+    // connect_timeout is connect with timeout bounding each exchange, including
+    // the first one
+    pub fn connect_timeout(
+        endpoint: Endpoint,
+        timeout: Duration,
+    ) -> Result<RemoteSigner, SignerError> {
+        let res = request(&endpoint, timeout, &json!({"method": "public_key"}))?;
+        let public_key = match res.get("public_key").and_then(|k| k.as_str()) {
+            Some(r) => r,
+            None => return Err(unavailable("missing public_key")),
+        };
+        // the key comes from the daemon: parse it before use, and keep it
+        // uncompressed as actions carry it
+        let public_key = match hex::decode(public_key)
+            .ok()
+            .and_then(|b| secp256k1::PublicKey::from_slice(&b).ok())
+        {
+            Some(r) => hex::encode(&r.serialize_uncompressed()[..]),
+            None => return Err(SignerError::CryptoError(crypto::Error::InvalidPublicKey)),
+        };
+        let hash = key::public_key_hash(public_key.clone())?;
+        let address = match address::from_bytes(&hash.0) {
+            Ok(r) => r,
+            Err(_) => return Err(SignerError::CryptoError(crypto::Error::InvalidPublicKey)),
+        };
+        Ok(RemoteSigner {
+            endpoint,
+            timeout,
+            public_key,
+            address,
+        })
+    }
+    // endpoint returns where the signing daemon listens
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+    // set_timeout sets the connect, read and write timeout of each exchange
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn address(&self) -> AddrV1 {
+        self.address
+    }
+    fn public_key(&self) -> String {
+        self.public_key.clone()
+    }
+    // sign_digest asks the daemon for a signature and checks that it recovers
+    // to the daemon's key
+    fn sign_digest(&self, digest: &hash::Hash256b) -> Result<[u8; 65], SignerError> {
+        let res = request(
+            &self.endpoint,
+            self.timeout,
+            &json!({
+                "method": "sign_digest",
+                "digest": hex::encode(digest.0),
+            }),
+        )?;
+        let mut sig = [0u8; 65];
+        match res.get("signature").and_then(|s| s.as_str()) {
+            Some(s) if hex::decode_to_slice(s, &mut sig).is_ok() => (),
+            _ => return Err(SignerError::CryptoError(crypto::Error::InvalidSignature)),
+        }
+        match recover_signer(digest, &sig) {
+            Ok(addr) if addr == self.address => Ok(sig),
+            _ => Err(SignerError::CryptoError(crypto::Error::InvalidSignature)),
+        }
+    }
+}
+
+// SigningServer is a reference signing daemon serving the protocol with an
+// in-memory key
+pub struct SigningServer {
+    key: key::PrivKey,
+    timeout: Duration,
+}
+
+impl SigningServer {
+    // new serves signatures of key
+    pub fn new(key: key::PrivKey) -> SigningServer {
+        SigningServer {
+            key,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+    // set_timeout sets the read and write timeout of each connection, after
+    // which a client that does not finish its request is dropped
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+    // handle answers a single JSON request
+    pub fn handle(&self, request: &[u8]) -> Value {
+        let request: Value = match serde_json::from_slice(request) {
+            Ok(r) => r,
+            Err(e) => return json!({ "error": e.to_string() }),
+        };
+        match request.get("method").and_then(|m| m.as_str()) {
+            Some("public_key") => json!({ "public_key": self.key.public_key() }),
+            Some("sign_digest") => {
+                let mut digest = [0u8; 32];
+                match request.get("digest").and_then(|d| d.as_str()) {
+                    Some(d) if hex::decode_to_slice(d, &mut digest).is_ok() => {
+                        let sig = self.key.sign_digest(&hash::Hash256b(digest));
+                        json!({ "signature": hex::encode(&sig[..]) })
+                    }
+                    _ => json!({ "error": "invalid digest" }),
+                }
+            }
+            _ => json!({ "error": "unknown method" }),
+        }
+    }
+    // serve_unix answers the connections accepted by listener, each on its own
+    // thread; a failed or stalled connection does not stop the server
+    #[cfg(unix)]
+    pub fn serve_unix(&self, listener: UnixListener) -> io::Result<()> {
+        thread::scope(|s| {
+            for stream in listener.incoming() {
+                let stream = stream?;
+                s.spawn(move || self.answer_unix(stream));
+            }
+            Ok(())
+        })
+    }
+    // serve_http answers the HTTP POST requests accepted by listener, each on
+    // its own thread; a failed or stalled connection does not stop the server
+    pub fn serve_http(&self, listener: TcpListener) -> io::Result<()> {
+        thread::scope(|s| {
+            for stream in listener.incoming() {
+                let stream = stream?;
+                s.spawn(move || self.answer_http(stream));
+            }
+            Ok(())
+        })
+    }
+
+    #[cfg(unix)]
+    fn answer_unix(&self, mut stream: UnixStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        let mut line = Vec::new();
+        BufReader::new(&stream)
+            .take(MAX_MESSAGE_SIZE)
+            .read_until(b'\n', &mut line)?;
+        writeln!(stream, "{}", self.handle(&line))
+    }
+
+    fn answer_http(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        let mut reader = BufReader::new((&stream).take(MAX_MESSAGE_SIZE));
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let length = read_headers(&mut reader)?;
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        let (status, res) = if request_line.starts_with("POST ") {
+            let res = self.handle(&body);
+            match res.get("error") {
+                Some(_) => ("400 Bad Request", res),
+                None => ("200 OK", res),
+            }
+        } else {
+            ("405 Method Not Allowed", json!({ "error": "POST only" }))
+        };
+        let res = res.to_string();
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            res.len(),
+            res
+        )
+    }
+}
+
+// request exchanges request for the answer of the signer at endpoint
+fn request(endpoint: &Endpoint, timeout: Duration, request: &Value) -> Result<Value, SignerError> {
+    let body = match endpoint {
+        #[cfg(unix)]
+        Endpoint::Unix(path) => request_unix(path, timeout, request),
+        Endpoint::Http { host, path } => request_http(host, path, timeout, request),
+    };
+    let body = match body {
+        Ok(r) => r,
+        Err(e) => return Err(unavailable(&e.to_string())),
+    };
+    let res: Value = match serde_json::from_slice(&body) {
+        Ok(r) => r,
+        Err(e) => return Err(unavailable(&e.to_string())),
+    };
+    match res.get("error").and_then(|e| e.as_str()) {
+        Some(e) => Err(SignerError::Rejected(String::from(e))),
+        None => Ok(res),
+    }
+}
+
+#[cfg(unix)]
+fn request_unix(path: &Path, timeout: Duration, request: &Value) -> io::Result<Vec<u8>> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    writeln!(stream, "{}", request)?;
+    let mut line = Vec::new();
+    BufReader::new(stream)
+        .take(MAX_MESSAGE_SIZE)
+        .read_until(b'\n', &mut line)?;
+    Ok(line)
+}
+
+fn request_http(host: &str, path: &str, timeout: Duration, request: &Value) -> io::Result<Vec<u8>> {
+    let mut stream = connect_tcp(host, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    let body = request.to_string();
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    )?;
+    let mut reader = BufReader::new(stream.take(MAX_MESSAGE_SIZE));
+    let mut status = String::new();
+    reader.read_line(&mut status)?;
+    let length = read_headers(&mut reader)?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    // error responses carry a JSON error in their body
+    if !status.starts_with("HTTP/1.1 200") && !status.starts_with("HTTP/1.1 400") {
+        return Err(io::Error::new(io::ErrorKind::Other, status.trim()));
+    }
+    Ok(body)
+}
+
+// connect_tcp connects to the first address host resolves to that accepts
+// within timeout
+fn connect_tcp(host: &str, timeout: Duration) -> io::Result<TcpStream> {
+    let mut last = io::Error::new(io::ErrorKind::NotFound, "host not found");
+    for addr in host.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(r) => return Ok(r),
+            Err(e) => last = e,
+        }
+    }
+    Err(last)
+}
+
+// read_headers skips HTTP headers up to the blank line and returns the content length
+fn read_headers<R: BufRead>(reader: &mut R) -> io::Result<usize> {
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            return Ok(length);
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = match value.trim().parse::<u64>() {
+                    Ok(n) if n <= MAX_MESSAGE_SIZE => n as usize,
+                    _ => return Err(io::Error::from(io::ErrorKind::InvalidData)),
+                };
+            }
+        }
+    }
+}
+
+fn unavailable(msg: &str) -> SignerError {
+    SignerError::Unavailable(String::from(msg))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::action::{Envelope, Execution, VERSION};
+    use ethabi::Uint;
+
+    const PRIVATE_KEY: &str = "0806c458b262edd333a191e92f561aff338211ee3e18ab315a074a2d82aa343f";
+
+    fn server() -> SigningServer {
+        SigningServer::new(key::hex_string_to_private(String::from(PRIVATE_KEY)).unwrap())
+    }
+
+    fn check(signer: &RemoteSigner) {
+        let acc = account::hex_string_to_account(String::from(PRIVATE_KEY)).unwrap();
+        assert_eq!(signer.address(), acc.address());
+        assert_eq!(Signer::public_key(signer), acc.public_key());
+        let digest = hash::hash256b(b"IoTeX");
+        assert_eq!(
+            signer.sign_digest(&digest).unwrap()[..],
+            acc.sign(b"IoTeX")[..]
+        );
+        let sealed = signer
+            .sign_action(Envelope {
+                version: VERSION,
+                nonce: 1,
+                gas_limit: 10_000,
+                gas_price: Uint::from(1),
+                chain_id: 1,
+                execution: Execution {
+                    amount: Uint::zero(),
+                    contract: String::new(),
+                    data: vec![0x60],
+                },
+            })
+            .unwrap();
+        assert_eq!(sealed.sender().unwrap(), acc.address());
+    }
+
+    #[test]
+    fn test_handle() {
+        let server = server();
+        assert_eq!(
+            server.handle(br#"{"method":"sign_digest","digest":"00"}"#),
+            json!({"error": "invalid digest"})
+        );
+        assert_eq!(
+            server.handle(br#"{"method":"export"}"#),
+            json!({"error": "unknown method"})
+        );
+        assert!(server.handle(b"not json").get("error").is_some());
+    }
+
+    #[test]
+    fn test_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/sign", listener.local_addr().unwrap());
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || server().serve_http(listener));
+        // a client that never sends its request does not block the others
+        let _idle = TcpStream::connect(addr).unwrap();
+        check(&RemoteSigner::http(&url).unwrap());
+        assert!(RemoteSigner::http("https://localhost/sign").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_invalid_public_key() {
+        let path =
+            std::env::temp_dir().join(format!("iotex-bad-signer-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        thread::spawn(move || {
+            for (stream, public_key) in listener.incoming().zip(["", "00"].iter()) {
+                let mut stream = stream.unwrap();
+                let mut line = String::new();
+                BufReader::new(&stream).read_line(&mut line).unwrap();
+                writeln!(stream, "{}", json!({ "public_key": public_key })).unwrap();
+            }
+        });
+        for _ in 0..2 {
+            assert_eq!(
+                RemoteSigner::unix(&path).err(),
+                Some(SignerError::CryptoError(crypto::Error::InvalidPublicKey))
+            );
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix() {
+        let path = std::env::temp_dir().join(format!("iotex-signer-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        thread::spawn(move || server().serve_unix(listener));
+        let _idle = UnixStream::connect(&path).unwrap();
+        check(&RemoteSigner::unix(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            RemoteSigner::unix(&path),
+            Err(SignerError::Unavailable(_))
+        ));
+    }
+}
@@ -0,0 +1,395 @@
+// SharedAccounts is an Accounts store that can be shared between threads. It
+// signs by address without handing out keys, and hands out nonces so that
+// parallel senders from one account do not collide. Accounts can be named by
+// alias like those of Accounts.
+use super::address_book::{Directory, Metadata, Resolver, ToAddress};
+use super::*;
+use crate::action::{Envelope, SealedEnvelope};
+use crate::address::v1::AddrV1;
+use crate::rpc::Client;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+// Nonce is the next nonce of an account, None until fetched from the chain
+type Nonce = Arc<Mutex<Option<u64>>>;
+
+#[derive(Default)]
+pub struct SharedAccounts {
+    signers: RwLock<HashMap<AddrV1, Arc<dyn Signer>>>,
+    nonces: Mutex<HashMap<AddrV1, Nonce>>,
+    directory: RwLock<Directory>,
+}
+
+impl SharedAccounts {
+    pub fn new() -> SharedAccounts {
+        SharedAccounts::default()
+    }
+    // create creates a new account and returns its address
+    pub fn create(&self) -> Result<AddrV1, AccountError> {
+        let acc = account::new_account()?;
+        let addr = acc.address();
+        match self.add_account(acc) {
+            Some(e) => Err(e),
+            None => Ok(addr),
+        }
+    }
+    // add_account add an account
+    pub fn add_account(&self, acc: account) -> Option<AccountError> {
+        self.add_signer(Arc::new(acc))
+    }
+    // add_signer adds a signer, e.g. a remote or watch-only one
+    pub fn add_signer(&self, signer: Arc<dyn Signer>) -> Option<AccountError> {
+        let addr = signer.address();
+        let mut signers = self.signers.write().unwrap();
+        if signers.contains_key(&addr) {
+            return Some(AccountError::AccountExist(addr));
+        }
+        signers.insert(addr, signer);
+        self.directory.write().unwrap().insert(addr);
+        None
+    }
+    // remove_account removes an account along with its alias and labels, and
+    // forgets its nonce
+    pub fn remove_account<A: ToAddress>(&self, addr: A) {
+        let addr = match addr.to_address(self) {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        let mut signers = self.signers.write().unwrap();
+        signers.remove(&addr);
+        self.directory.write().unwrap().remove(addr);
+        self.nonces.lock().unwrap().remove(&addr);
+    }
+    pub fn contains<A: ToAddress>(&self, addr: A) -> bool {
+        match addr.to_address(self) {
+            Ok(r) => self.signers.read().unwrap().contains_key(&r),
+            Err(_) => false,
+        }
+    }
+    // addresses returns the addresses of the accounts, in no particular order
+    pub fn addresses(&self) -> Vec<AddrV1> {
+        self.signers.read().unwrap().keys().cloned().collect()
+    }
+    // get_signer returns the signer of addr, which can outlive its removal
+    pub fn get_signer<A: ToAddress>(&self, addr: A) -> Option<Arc<dyn Signer>> {
+        let addr = addr.to_address(self).ok()?;
+        self.signers.read().unwrap().get(&addr).cloned()
+    }
+    // sign_digest signs digest with the key of addr
+    pub fn sign_digest<A: ToAddress>(
+        &self,
+        addr: A,
+        digest: &hash::Hash256b,
+    ) -> Result<[u8; 65], AccountError> {
+        let addr = addr.to_address(self)?;
+        Ok(self.signer(addr)?.sign_digest(digest)?)
+    }
+    // sign_action seals envelope with the key of addr
+    pub fn sign_action<A: ToAddress>(
+        &self,
+        addr: A,
+        envelope: Envelope,
+    ) -> Result<SealedEnvelope, AccountError> {
+        let addr = addr.to_address(self)?;
+        Ok(self.signer(addr)?.sign_action(envelope)?)
+    }
+    // next_nonce reserves the next nonce of addr, asking client for the
+    // pending nonce the first time and counting locally afterwards
+    pub fn next_nonce<A: ToAddress, C: Client + ?Sized>(
+        &self,
+        addr: A,
+        client: &C,
+    ) -> Result<u64, AccountError> {
+        let addr = addr.to_address(self)?;
+        if let Some(next) = self.reserve_nonce(addr)? {
+            return Ok(next);
+        }
+        // the nonce is fetched without holding the lock, so that a slow node
+        // does not block other senders; one they set meanwhile is kept
+        let fetched = client.pending_nonce(&addr)?;
+        let nonce = self.nonce(addr)?;
+        let mut nonce = nonce.lock().unwrap();
+        let next = nonce.unwrap_or(fetched);
+        *nonce = Some(next + 1);
+        Ok(next)
+    }
+    // reserve_nonce reserves the next nonce of addr, or returns None if it has
+    // not been fetched or set yet
+    pub fn reserve_nonce<A: ToAddress>(&self, addr: A) -> Result<Option<u64>, AccountError> {
+        let addr = addr.to_address(self)?;
+        let nonce = self.nonce(addr)?;
+        let mut nonce = nonce.lock().unwrap();
+        let next = match *nonce {
+            Some(r) => r,
+            None => return Ok(None),
+        };
+        *nonce = Some(next + 1);
+        Ok(Some(next))
+    }
+    // set_nonce sets the next nonce of addr
+    pub fn set_nonce<A: ToAddress>(&self, addr: A, next: u64) -> Result<(), AccountError> {
+        let addr = addr.to_address(self)?;
+        *self.nonce(addr)?.lock().unwrap() = Some(next);
+        Ok(())
+    }
+    // reset_nonce forgets the nonce of addr, e.g. after an action was
+    // rejected, so that the next one is fetched from the chain
+    pub fn reset_nonce<A: ToAddress>(&self, addr: A) {
+        let addr = match addr.to_address(self) {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        if let Some(nonce) = self.nonces.lock().unwrap().get(&addr) {
+            *nonce.lock().unwrap() = None;
+        }
+    }
+    // sign_with_next_nonce seals envelope with the next nonce and the key of
+    // addr. If signing fails, the nonce is given back unless a later one was
+    // handed out meanwhile, in which case the error is a NonceGap
+    pub fn sign_with_next_nonce<A: ToAddress, C: Client + ?Sized>(
+        &self,
+        addr: A,
+        client: &C,
+        mut envelope: Envelope,
+    ) -> Result<SealedEnvelope, AccountError> {
+        let addr = addr.to_address(self)?;
+        let signer = self.signer(addr)?;
+        let next = self.next_nonce(addr, client)?;
+        envelope.nonce = next;
+        match signer.sign_action(envelope) {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let nonce = self.nonce(addr)?;
+                let mut nonce = nonce.lock().unwrap();
+                if *nonce != Some(next + 1) {
+                    return Err(AccountError::NonceGap(next, e));
+                }
+                *nonce = Some(next);
+                Err(e.into())
+            }
+        }
+    }
+    // metadata returns the alias and labels of an account
+    pub fn metadata<A: ToAddress>(&self, addr: A) -> Option<Metadata> {
+        let addr = addr.to_address(self).ok()?;
+        self.directory.read().unwrap().get(addr).cloned()
+    }
+    // set_alias sets or, with None, clears the alias of an account
+    pub fn set_alias<A: ToAddress>(
+        &self,
+        addr: A,
+        alias: Option<&str>,
+    ) -> Result<(), AccountError> {
+        let addr = addr.to_address(self)?;
+        self.directory.write().unwrap().set_alias(addr, alias)
+    }
+    // set_label sets or, with None, clears a label of an account
+    pub fn set_label<A: ToAddress>(
+        &self,
+        addr: A,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), AccountError> {
+        let addr = addr.to_address(self)?;
+        self.directory.write().unwrap().set_label(addr, key, value)
+    }
+
+    fn signer(&self, addr: AddrV1) -> Result<Arc<dyn Signer>, AccountError> {
+        match self.get_signer(addr) {
+            Some(r) => Ok(r),
+            None => Err(AccountError::AccountNotExist(addr)),
+        }
+    }
+
+    // nonce returns the nonce of addr, which must be in the store
+    fn nonce(&self, addr: AddrV1) -> Result<Nonce, AccountError> {
+        if !self.contains(addr) {
+            return Err(AccountError::AccountNotExist(addr));
+        }
+        Ok(self.nonces.lock().unwrap().entry(addr).or_default().clone())
+    }
+}
+
+impl Resolver for SharedAccounts {
+    fn resolve(&self, name: &str) -> Option<AddrV1> {
+        self.directory.read().unwrap().resolve(name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::action::{Execution, VERSION};
+    use crate::address::Address;
+    use crate::rpc::mock::MockClient;
+    use ethabi::Uint;
+    use std::thread;
+
+    // Racing fails to sign after another sender reserved the next nonce
+    struct Racing {
+        address: AddrV1,
+        nonce: Nonce,
+    }
+
+    impl Signer for Racing {
+        fn address(&self) -> AddrV1 {
+            self.address
+        }
+        fn public_key(&self) -> String {
+            String::new()
+        }
+        fn sign_digest(&self, _digest: &hash::Hash256b) -> Result<[u8; 65], SignerError> {
+            let mut nonce = self.nonce.lock().unwrap();
+            *nonce = nonce.map(|n| n + 1);
+            Err(SignerError::Unavailable(String::from("offline")))
+        }
+    }
+
+    // Interleaved sets the nonce of address while its pending nonce is fetched
+    struct Interleaved<'a> {
+        accounts: &'a SharedAccounts,
+        address: AddrV1,
+    }
+
+    impl Client for Interleaved<'_> {
+        fn pending_nonce(&self, _addr: &AddrV1) -> Result<u64, rpc::RpcError> {
+            self.accounts.set_nonce(self.address, 9).unwrap();
+            Ok(3)
+        }
+        fn read_contract(
+            &self,
+            _execution: &Execution,
+            _caller: &AddrV1,
+            _gas_limit: u64,
+        ) -> Result<rpc::ReadContractResponse, rpc::RpcError> {
+            unimplemented!()
+        }
+        fn send_action(&self, _action: &SealedEnvelope) -> Result<hash::Hash256b, rpc::RpcError> {
+            unimplemented!()
+        }
+        fn get_receipt(
+            &self,
+            _hash: &hash::Hash256b,
+        ) -> Result<Option<crate::action::Receipt>, rpc::RpcError> {
+            unimplemented!()
+        }
+    }
+
+    fn envelope() -> Envelope {
+        Envelope {
+            version: VERSION,
+            nonce: 0,
+            gas_limit: 10_000,
+            gas_price: Uint::from(1),
+            chain_id: 1,
+            execution: Execution {
+                amount: Uint::zero(),
+                contract: String::new(),
+                data: vec![0x60],
+            },
+        }
+    }
+
+    #[test]
+    fn test_shared_accounts() {
+        let accounts = Arc::new(SharedAccounts::new());
+        let addr = accounts.create().unwrap();
+        let watched = account::new_account().unwrap().address();
+        assert_eq!(accounts.add_signer(Arc::new(WatchOnly::new(watched))), None);
+        assert_eq!(accounts.addresses().len(), 2);
+
+        let client = MockClient::new();
+        client.nonce.set(5);
+        let sealed = accounts
+            .sign_with_next_nonce(addr, &client, envelope())
+            .unwrap();
+        assert_eq!(sealed.envelope().nonce, 5);
+        assert_eq!(sealed.sender().unwrap(), addr);
+        // accounts can be named by alias or encoded address
+        accounts.set_alias(addr, Some("hot")).unwrap();
+        assert_eq!(accounts.metadata(addr).unwrap().alias.unwrap(), "hot");
+        let sealed = accounts
+            .sign_with_next_nonce("hot", &client, envelope())
+            .unwrap();
+        assert_eq!(sealed.envelope().nonce, 6);
+        assert_eq!(sealed.sender().unwrap(), addr);
+        assert!(accounts.contains(addr.string()));
+        assert_eq!(
+            accounts.set_nonce("cold", 1),
+            Err(AccountError::UnknownName(String::from("cold")))
+        );
+        assert_eq!(
+            accounts.sign_with_next_nonce(watched, &client, envelope()),
+            Err(AccountError::SignerError(SignerError::WatchOnly(watched)))
+        );
+        // the failed nonce is given back
+        assert_eq!(accounts.reserve_nonce(watched), Ok(Some(5)));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let accounts = Arc::clone(&accounts);
+                thread::spawn(move || {
+                    (0..25)
+                        .map(|_| {
+                            let mut e = envelope();
+                            e.nonce = accounts.reserve_nonce(addr).unwrap().unwrap();
+                            accounts.sign_action(addr, e).unwrap().envelope().nonce
+                        })
+                        .collect::<Vec<u64>>()
+                })
+            })
+            .collect();
+        let mut nonces: Vec<u64> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        nonces.sort();
+        assert_eq!(nonces, (7..107).collect::<Vec<u64>>());
+
+        accounts.reset_nonce(addr);
+        assert_eq!(accounts.next_nonce(addr, &client).unwrap(), 5);
+        accounts.remove_account(addr);
+        assert_eq!(
+            accounts.sign_digest(addr, &hash::hash256b(b"IoTeX")),
+            Err(AccountError::AccountNotExist(addr))
+        );
+        assert_eq!(
+            accounts.set_nonce(addr, 1),
+            Err(AccountError::AccountNotExist(addr))
+        );
+        assert_eq!(
+            accounts.reserve_nonce(addr),
+            Err(AccountError::AccountNotExist(addr))
+        );
+        assert!(!accounts.nonces.lock().unwrap().contains_key(&addr));
+
+        // a nonce reserved by another sender while signing fails is kept
+        let racing = account::new_account().unwrap().address();
+        let nonce = Nonce::default();
+        let signer = Racing {
+            address: racing,
+            nonce: Arc::clone(&nonce),
+        };
+        assert_eq!(accounts.add_signer(Arc::new(signer)), None);
+        accounts.nonces.lock().unwrap().insert(racing, nonce);
+        accounts.set_nonce(racing, 7).unwrap();
+        assert_eq!(
+            accounts.sign_with_next_nonce(racing, &client, envelope()),
+            Err(AccountError::NonceGap(
+                7,
+                SignerError::Unavailable(String::from("offline"))
+            ))
+        );
+        assert_eq!(accounts.reserve_nonce(racing), Ok(Some(9)));
+
+        // the store is not locked while the pending nonce is fetched, and a
+        // nonce set meanwhile wins over the fetched one
+        let client = Interleaved {
+            accounts: &accounts,
+            address: racing,
+        };
+        accounts.reset_nonce(racing);
+        assert_eq!(accounts.next_nonce(racing, &client), Ok(9));
+        assert_eq!(accounts.reserve_nonce(racing), Ok(Some(10)));
+    }
+}
@@ -0,0 +1,138 @@
+use super::*;
+use crate::action::{Envelope, SealedEnvelope};
+use crate::address::{v1::AddrV1, Address};
+use std::sync::Arc;
+use std::{error, fmt};
+
+// Signer holds or reaches the key of an address and signs on its behalf. The
+// local account implements it with an in-memory key; key custody systems can
+// implement it without ever exposing the key.
+pub trait Signer: Send + Sync {
+    // address returns the IoTeX address of the key
+    fn address(&self) -> AddrV1;
+    // public_key returns the hex encoded uncompressed public key
+    fn public_key(&self) -> String;
+    // sign_digest signs a pre-computed 32 byte digest
+    fn sign_digest(&self, digest: &hash::Hash256b) -> Result<[u8; 65], SignerError>;
+    // sign_action seals envelope with a signature of its hash
+    fn sign_action(&self, envelope: Envelope) -> Result<SealedEnvelope, SignerError> {
+        envelope.sign(self)
+    }
+    // watch_only tells whether signing always fails because the key is not held
+    fn watch_only(&self) -> bool {
+        false
+    }
+}
+
+impl Signer for account {
+    fn address(&self) -> AddrV1 {
+        self.address
+    }
+    fn public_key(&self) -> String {
+        self.private.public_key()
+    }
+    fn sign_digest(&self, digest: &hash::Hash256b) -> Result<[u8; 65], SignerError> {
+        Ok(self.private.sign_digest(digest))
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Box<S> {
+    fn address(&self) -> AddrV1 {
+        (**self).address()
+    }
+    fn public_key(&self) -> String {
+        (**self).public_key()
+    }
+    fn sign_digest(&self, digest: &hash::Hash256b) -> Result<[u8; 65], SignerError> {
+        (**self).sign_digest(digest)
+    }
+    fn watch_only(&self) -> bool {
+        (**self).watch_only()
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Arc<S> {
+    fn address(&self) -> AddrV1 {
+        (**self).address()
+    }
+    fn public_key(&self) -> String {
+        (**self).public_key()
+    }
+    fn sign_digest(&self, digest: &hash::Hash256b) -> Result<[u8; 65], SignerError> {
+        (**self).sign_digest(digest)
+    }
+    fn watch_only(&self) -> bool {
+        (**self).watch_only()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum SignerError {
+    CryptoError(crypto::Error),
+    // Unavailable is a signer that could not be reached or answered garbage
+    Unavailable(String),
+    // Rejected is a signer that refused to sign
+    Rejected(String),
+    // WatchOnly is an account whose key is not held
+    WatchOnly(AddrV1),
+}
+
+impl From<crypto::Error> for SignerError {
+    fn from(e: crypto::Error) -> Self {
+        SignerError::CryptoError(e)
+    }
+}
+
+impl fmt::Display for SignerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignerError::CryptoError(e) => write!(f, "crypto error, {}", e),
+            SignerError::Unavailable(e) => write!(f, "signer unavailable, {}", e),
+            SignerError::Rejected(e) => write!(f, "signer rejected request, {}", e),
+            SignerError::WatchOnly(a) => write!(f, "watch-only account {}", a.string()),
+        }
+    }
+}
+
+impl error::Error for SignerError {
+    fn description(&self) -> &str {
+        match *self {
+            SignerError::CryptoError(_) => "crypto error",
+            SignerError::Unavailable(_) => "signer unavailable",
+            SignerError::Rejected(_) => "signer rejected request",
+            SignerError::WatchOnly(_) => "watch-only account",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::action::{Execution, VERSION};
+    use ethabi::Uint;
+
+    #[test]
+    fn test_signer() {
+        let acc = account::new_account().unwrap();
+        let signers: Vec<Box<dyn Signer>> =
+            vec![Box::new(acc.clone()), Box::new(Arc::new(acc.clone()))];
+        let envelope = Envelope {
+            version: VERSION,
+            nonce: 3,
+            gas_limit: 10_000,
+            gas_price: Uint::from(1),
+            chain_id: 1,
+            execution: Execution {
+                amount: Uint::zero(),
+                contract: acc.address().string(),
+                data: Vec::new(),
+            },
+        };
+        for signer in signers.iter() {
+            assert_eq!(signer.address(), acc.address());
+            let sealed = signer.sign_action(envelope.clone()).unwrap();
+            assert_eq!(sealed.sender().unwrap(), acc.address());
+            assert_eq!(sealed.signature()[..], acc.sign(&envelope.serialize())[..]);
+        }
+    }
+}
@@ -0,0 +1,477 @@
+use super::*;
+use crate::address::{v1::AddrV1, Address};
+use ethabi::Uint;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::{error, fmt};
+
+// EIP712_DOMAIN is the name of the domain type
+pub const EIP712_DOMAIN: &str = "EIP712Domain";
+
+// DOMAIN_FIELDS are the domain fields in canonical order, used when the types
+// do not declare EIP712Domain
+const DOMAIN_FIELDS: [(&str, &str); 5] = [
+    ("name", "string"),
+    ("version", "string"),
+    ("chainId", "uint256"),
+    ("verifyingContract", "address"),
+    ("salt", "bytes32"),
+];
+
+// Field is a member of a struct type
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub kind: String,
+}
+
+// TypedData is EIP-712 typed structured data, as passed to eth_signTypedData
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedData {
+    pub types: BTreeMap<String, Vec<Field>>,
+    pub primary_type: String,
+    pub domain: Map<String, Value>,
+    pub message: Map<String, Value>,
+}
+
+impl TypedData {
+    // from_json parses the JSON {types, primaryType, domain, message} format
+    pub fn from_json(json: &str) -> Result<TypedData, TypedDataError> {
+        let value: Value = match serde_json::from_str(json) {
+            Ok(r) => r,
+            Err(e) => return Err(TypedDataError::InvalidJson(e.to_string())),
+        };
+        let mut types = BTreeMap::new();
+        for (name, fields) in object(&value, "types")? {
+            let fields = match fields.as_array() {
+                Some(r) => r,
+                None => return Err(TypedDataError::InvalidJson(name.clone())),
+            };
+            let mut members = Vec::new();
+            for field in fields {
+                match (
+                    field.get("name").and_then(|n| n.as_str()),
+                    field.get("type").and_then(|t| t.as_str()),
+                ) {
+                    (Some(n), Some(t)) => members.push(Field {
+                        name: String::from(n),
+                        kind: String::from(t),
+                    }),
+                    _ => return Err(TypedDataError::InvalidJson(name.clone())),
+                }
+            }
+            types.insert(name.clone(), members);
+        }
+        let primary_type = match value.get("primaryType").and_then(|t| t.as_str()) {
+            Some(r) => String::from(r),
+            None => return Err(TypedDataError::InvalidJson(String::from("primaryType"))),
+        };
+        let domain = object(&value, "domain")?.clone();
+        let message = object(&value, "message")?.clone();
+        if !types.contains_key(EIP712_DOMAIN) {
+            let fields = DOMAIN_FIELDS
+                .iter()
+                .filter(|(name, _)| domain.contains_key(*name))
+                .map(|(name, kind)| Field {
+                    name: String::from(*name),
+                    kind: String::from(*kind),
+                })
+                .collect();
+            types.insert(String::from(EIP712_DOMAIN), fields);
+        }
+        if !types.contains_key(&primary_type) {
+            return Err(TypedDataError::UnknownType(primary_type));
+        }
+        Ok(TypedData {
+            types,
+            primary_type,
+            domain,
+            message,
+        })
+    }
+    // encode_type returns the type encoding of name followed by the types it
+    // references, e.g. Mail(Person from,Person to,string contents)Person(string name,address wallet)
+    pub fn encode_type(&self, name: &str) -> Result<String, TypedDataError> {
+        let mut deps = BTreeSet::new();
+        self.dependencies(name, &mut deps)?;
+        deps.remove(name);
+        let mut encoded = self.format_type(name)?;
+        for dep in deps {
+            encoded.push_str(&self.format_type(&dep)?);
+        }
+        Ok(encoded)
+    }
+    // type_hash returns the hash of the type encoding of name
+    pub fn type_hash(&self, name: &str) -> Result<hash::Hash256b, TypedDataError> {
+        Ok(hash::hash256b(self.encode_type(name)?.as_bytes()))
+    }
+    // hash_struct returns the hash of data as a value of the struct type name
+    pub fn hash_struct(
+        &self,
+        name: &str,
+        data: &Map<String, Value>,
+    ) -> Result<hash::Hash256b, TypedDataError> {
+        let fields = self.fields(name)?;
+        let mut encoded = self.type_hash(name)?.0.to_vec();
+        for field in fields {
+            match data.get(&field.name) {
+                Some(value) => encoded.extend_from_slice(&self.encode_value(&field.kind, value)?),
+                None => return Err(TypedDataError::InvalidValue(field.name.clone())),
+            }
+        }
+        Ok(hash::hash256b(&encoded))
+    }
+    // domain_separator returns the hash of the domain
+    pub fn domain_separator(&self) -> Result<hash::Hash256b, TypedDataError> {
+        self.hash_struct(EIP712_DOMAIN, &self.domain)
+    }
+    // message_hash returns the hash of the message as a value of the primary type
+    pub fn message_hash(&self) -> Result<hash::Hash256b, TypedDataError> {
+        self.hash_struct(&self.primary_type, &self.message)
+    }
+    // signing_hash returns the signed digest,
+    // keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))
+    pub fn signing_hash(&self) -> Result<hash::Hash256b, TypedDataError> {
+        let mut encoded = vec![0x19, 0x01];
+        encoded.extend_from_slice(&self.domain_separator()?.0);
+        encoded.extend_from_slice(&self.message_hash()?.0);
+        Ok(hash::hash256b(&encoded))
+    }
+    // sign signs the data with key; the recovery ID of the signature is 27 or 28
+    pub fn sign(&self, key: &key::PrivKey) -> Result<[u8; 65], TypedDataError> {
+        let mut sig = key.sign_digest(&self.signing_hash()?);
+        sig[64] += 27;
+        Ok(sig)
+    }
+    // recover returns the address that signed the data
+    pub fn recover(&self, sig: &[u8]) -> Result<AddrV1, TypedDataError> {
+        match recover_signer(&self.signing_hash()?, sig) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(TypedDataError::AccountError(e)),
+        }
+    }
+
+    fn fields(&self, name: &str) -> Result<&[Field], TypedDataError> {
+        match self.types.get(name) {
+            Some(r) => Ok(r),
+            None => Err(TypedDataError::UnknownType(String::from(name))),
+        }
+    }
+
+    fn format_type(&self, name: &str) -> Result<String, TypedDataError> {
+        let fields: Vec<String> = self
+            .fields(name)?
+            .iter()
+            .map(|f| format!("{} {}", f.kind, f.name))
+            .collect();
+        Ok(format!("{}({})", name, fields.join(",")))
+    }
+
+    // dependencies collects name and the struct types it references, recursively
+    fn dependencies(&self, name: &str, deps: &mut BTreeSet<String>) -> Result<(), TypedDataError> {
+        if !deps.insert(String::from(name)) {
+            return Ok(());
+        }
+        for field in self.fields(name)? {
+            let base = base_type(&field.kind);
+            if self.types.contains_key(base) {
+                self.dependencies(base, deps)?;
+            }
+        }
+        Ok(())
+    }
+
+    // encode_value encodes value of type kind into a 32 byte word: atomic values
+    // are padded, dynamic values, structs and arrays are hashed
+    fn encode_value(&self, kind: &str, value: &Value) -> Result<[u8; 32], TypedDataError> {
+        let invalid = || TypedDataError::InvalidValue(value.to_string());
+        if let Some(open) = kind.rfind('[') {
+            if !kind.ends_with(']') {
+                return Err(unknown(kind));
+            }
+            let (inner, len) = (&kind[..open], &kind[open + 1..kind.len() - 1]);
+            let items = value.as_array().ok_or_else(invalid)?;
+            if !len.is_empty() && len.parse::<usize>() != Ok(items.len()) {
+                return Err(invalid());
+            }
+            let mut encoded = Vec::with_capacity(32 * items.len());
+            for item in items {
+                encoded.extend_from_slice(&self.encode_value(inner, item)?);
+            }
+            return Ok(hash::hash256b(&encoded).0);
+        }
+        if self.types.contains_key(kind) {
+            let data = value.as_object().ok_or_else(invalid)?;
+            return Ok(self.hash_struct(kind, data)?.0);
+        }
+        let mut word = [0u8; 32];
+        match kind {
+            "string" => word = hash::hash256b(value.as_str().ok_or_else(invalid)?.as_bytes()).0,
+            "bytes" => word = hash::hash256b(&parse_hex(value).ok_or_else(invalid)?).0,
+            "bool" => word[31] = value.as_bool().ok_or_else(invalid)? as u8,
+            "address" => {
+                let s = value.as_str().ok_or_else(invalid)?;
+                let bytes = match address::from_string(s) {
+                    Ok(addr) => addr.bytes().to_vec(),
+                    Err(_) => parse_hex(value)
+                        .filter(|b| b.len() == 20)
+                        .ok_or_else(invalid)?,
+                };
+                word[12..].copy_from_slice(&bytes);
+            }
+            _ if kind.starts_with("bytes") => {
+                let size = parse_size(&kind[5..], 32).ok_or_else(|| unknown(kind))?;
+                let bytes = parse_hex(value)
+                    .filter(|b| b.len() == size)
+                    .ok_or_else(invalid)?;
+                word[..size].copy_from_slice(&bytes);
+            }
+            _ if kind.starts_with("uint") => {
+                let size = parse_size(&kind[4..], 256).ok_or_else(|| unknown(kind))?;
+                let (negative, v) = parse_integer(value).ok_or_else(invalid)?;
+                if negative || v.bits() > size * 8 {
+                    return Err(invalid());
+                }
+                v.to_big_endian(&mut word);
+            }
+            _ if kind.starts_with("int") => {
+                let size = parse_size(&kind[3..], 256).ok_or_else(|| unknown(kind))?;
+                let (negative, v) = parse_integer(value).ok_or_else(invalid)?;
+                let limit = Uint::one() << (size * 8 - 1);
+                if (negative && v > limit) || (!negative && v >= limit) {
+                    return Err(invalid());
+                }
+                let v = if negative {
+                    (!v).overflowing_add(Uint::one()).0
+                } else {
+                    v
+                };
+                v.to_big_endian(&mut word);
+            }
+            _ => return Err(unknown(kind)),
+        }
+        Ok(word)
+    }
+}
+
+// base_type strips the array dimensions of kind
+fn base_type(kind: &str) -> &str {
+    match kind.find('[') {
+        Some(i) => &kind[..i],
+        None => kind,
+    }
+}
+
+// parse_size parses the bit size of uintN/intN or the byte size of bytesN into bytes
+fn parse_size(suffix: &str, max: usize) -> Option<usize> {
+    let size: usize = suffix.parse().ok()?;
+    match max {
+        32 if (1..=32).contains(&size) => Some(size),
+        256 if (8..=256).contains(&size) && size % 8 == 0 => Some(size / 8),
+        _ => None,
+    }
+}
+
+// parse_integer parses a JSON number, decimal string or 0x hex string into its
+// sign and magnitude
+fn parse_integer(value: &Value) -> Option<(bool, Uint)> {
+    if let Some(v) = value.as_u64() {
+        return Some((false, Uint::from(v)));
+    }
+    if let Some(v) = value.as_i64() {
+        return Some((v < 0, Uint::from(v.unsigned_abs())));
+    }
+    let s = value.as_str()?;
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(r) => (true, r),
+        None => (false, s),
+    };
+    let v = match digits.strip_prefix("0x") {
+        Some(h) => {
+            let h = if h.len() % 2 == 1 {
+                format!("0{}", h)
+            } else {
+                String::from(h)
+            };
+            let bytes = hex::decode(h).ok()?;
+            if bytes.len() > 32 {
+                return None;
+            }
+            Uint::from(&bytes[..])
+        }
+        None if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) => {
+            Uint::from_dec_str(digits).ok()?
+        }
+        None => return None,
+    };
+    Some((negative, v))
+}
+
+fn parse_hex(value: &Value) -> Option<Vec<u8>> {
+    hex::decode(value.as_str()?.strip_prefix("0x")?).ok()
+}
+
+fn object<'a>(value: &'a Value, key: &str) -> Result<&'a Map<String, Value>, TypedDataError> {
+    match value.get(key).and_then(|v| v.as_object()) {
+        Some(r) => Ok(r),
+        None => Err(TypedDataError::InvalidJson(String::from(key))),
+    }
+}
+
+fn unknown(kind: &str) -> TypedDataError {
+    TypedDataError::UnknownType(String::from(kind))
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum TypedDataError {
+    InvalidJson(String),
+    UnknownType(String),
+    InvalidValue(String),
+    AccountError(AccountError),
+}
+
+impl fmt::Display for TypedDataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TypedDataError::InvalidJson(e) => write!(f, "invalid typed data json, {}", e),
+            TypedDataError::UnknownType(t) => write!(f, "unknown type {}", t),
+            TypedDataError::InvalidValue(v) => write!(f, "invalid value {}", v),
+            TypedDataError::AccountError(e) => write!(f, "account error, {}", e),
+        }
+    }
+}
+
+impl error::Error for TypedDataError {
+    fn description(&self) -> &str {
+        match *self {
+            TypedDataError::InvalidJson(_) => "invalid typed data json",
+            TypedDataError::UnknownType(_) => "unknown type",
+            TypedDataError::InvalidValue(_) => "invalid value",
+            TypedDataError::AccountError(_) => "account error",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // MAIL is the example of the EIP-712 specification
+    const MAIL: &str = r#"{
+        "types": {
+            "EIP712Domain": [
+                {"name": "name", "type": "string"},
+                {"name": "version", "type": "string"},
+                {"name": "chainId", "type": "uint256"},
+                {"name": "verifyingContract", "type": "address"}
+            ],
+            "Person": [
+                {"name": "name", "type": "string"},
+                {"name": "wallet", "type": "address"}
+            ],
+            "Mail": [
+                {"name": "from", "type": "Person"},
+                {"name": "to", "type": "Person"},
+                {"name": "contents", "type": "string"}
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+            "to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    #[test]
+    fn test_typed_data() {
+        let data = TypedData::from_json(MAIL).unwrap();
+        assert_eq!(
+            data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            hex::encode(data.type_hash("Mail").unwrap().0),
+            "a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+        );
+        assert_eq!(
+            hex::encode(data.message_hash().unwrap().0),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        assert_eq!(
+            hex::encode(data.domain_separator().unwrap().0),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hex::encode(data.signing_hash().unwrap().0),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+
+        // the signer of the specification is keccak256("cow")
+        let key = hash::hash256b(b"cow");
+        let act = account::private_key_to_account(key::PrivKey::from_slice(&key.0).unwrap());
+        let sig = act.sign_typed_data(&data).unwrap();
+        assert_eq!(
+            hex::encode(&sig[..]),
+            "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c"
+        );
+        let signer = data.recover(&sig).unwrap();
+        assert_eq!(signer, act.address());
+        assert_eq!(
+            hex::encode(signer.bytes()),
+            "cd2a3d9f938e13cd947ec05abc7fe734df8dd826"
+        );
+
+        // the domain type is inferred when missing, and addresses may be io1
+        let mut value: Value = serde_json::from_str(MAIL).unwrap();
+        value["types"]
+            .as_object_mut()
+            .unwrap()
+            .remove(EIP712_DOMAIN);
+        value["message"]["from"]["wallet"] = Value::String(act.address().string());
+        let inferred = TypedData::from_json(&value.to_string()).unwrap();
+        assert_eq!(inferred.signing_hash(), data.signing_hash());
+
+        let mut invalid = data.clone();
+        invalid.message.remove("contents");
+        assert_eq!(
+            invalid.message_hash(),
+            Err(TypedDataError::InvalidValue(String::from("contents")))
+        );
+    }
+
+    #[test]
+    fn test_encode_value() {
+        let data = TypedData::from_json(MAIL).unwrap();
+        let word = |kind: &str, value: Value| data.encode_value(kind, &value);
+        assert_eq!(word("int8", Value::from(-1)).unwrap(), [0xff; 32]);
+        assert!(word("int8", Value::from(128)).is_err());
+        assert!(word("uint8", Value::from("0x100")).is_err());
+        assert_eq!(word("uint16", Value::from("0x0100")).unwrap()[30], 1);
+        assert_eq!(
+            word("bytes2", Value::from("0x1234")).unwrap()[..2],
+            [0x12, 0x34]
+        );
+        assert!(word("bytes2", Value::from("0x12")).is_err());
+        assert!(word("uint7", Value::from(1)).is_err());
+        assert_eq!(
+            word("uint256[2]", serde_json::json!([1, "2"])).unwrap(),
+            hash::hash256b(&ethabi::encode(&[
+                ethabi::Token::Uint(Uint::from(1)),
+                ethabi::Token::Uint(Uint::from(2))
+            ]))
+            .0
+        );
+        assert!(word("uint256[3]", serde_json::json!([1, 2])).is_err());
+        assert_eq!(
+            word("uint256[", serde_json::json!([1])),
+            Err(TypedDataError::UnknownType(String::from("uint256[")))
+        );
+    }
+}
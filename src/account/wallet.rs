@@ -0,0 +1,410 @@
+// Wallet is a directory of encrypted keystores, one <address>.json per
+// account named by the hex of the address bytes, so that it does not depend on
+// the network prefix, and an index.json listing them. It is locked until unlocked by
+// password, and behaves like Accounts once unlocked. Aliases and labels are
+// kept in the index, so they are available while the wallet is locked.
+use super::accounts::Accounts;
+use super::address_book::{Directory, Metadata, Resolver, ToAddress};
+use super::keystore::{self, KdfParams, KeystoreError};
+use super::*;
+use crate::address::{v1::AddrV1, Address};
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::{error, fmt};
+
+const INDEX_FILE: &str = "index.json";
+// version 1 indexes list plain addresses, version 2 adds aliases and labels
+const INDEX_VERSION: u64 = 2;
+
+pub struct Wallet {
+    dir: PathBuf,
+    params: KdfParams,
+    index: Vec<AddrV1>,
+    directory: Directory,
+    unlocked: Option<Unlocked>,
+}
+
+struct Unlocked {
+    password: Password,
+    accounts: Accounts,
+}
+
+// Password is wiped when the wallet is locked
+struct Password(Vec<u8>);
+
+impl Drop for Password {
+    fn drop(&mut self) {
+        key::wipe(&mut self.0);
+    }
+}
+
+impl Wallet {
+    // open opens the wallet in dir, creating the directory if needed; the
+    // wallet starts locked
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Wallet, WalletError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let (directory, index) = match fs::read(dir.join(INDEX_FILE)) {
+            Ok(r) => parse_index(&r)?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (Directory::new(), Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Wallet {
+            dir,
+            params: KdfParams::default(),
+            index,
+            directory,
+            unlocked: None,
+        })
+    }
+    // set_kdf_params sets the scrypt cost of keystores written from now on
+    pub fn set_kdf_params(&mut self, params: KdfParams) {
+        self.params = params;
+    }
+    // addresses returns the addresses in the wallet, locked or not
+    pub fn addresses(&self) -> &[AddrV1] {
+        &self.index
+    }
+    pub fn is_locked(&self) -> bool {
+        self.unlocked.is_none()
+    }
+    // unlock decrypts every keystore with password; an empty wallet takes
+    // password as the password of the accounts added to it
+    pub fn unlock(&mut self, password: &str) -> Result<(), WalletError> {
+        let password = Password(password.as_bytes().to_vec());
+        let mut accounts = Accounts::new_accounts();
+        for addr in self.index.iter() {
+            let keystore = read_keystore(&self.dir.join(keystore_file(*addr)))?;
+            let acc = account::private_key_to_account(keystore::decrypt(&keystore, &password.0)?);
+            if acc.address() != *addr {
+                return Err(WalletError::KeystoreError(KeystoreError::InvalidKeystore(
+                    String::from("address"),
+                )));
+            }
+            accounts.add_account(acc);
+        }
+        self.unlocked = Some(Unlocked { password, accounts });
+        Ok(())
+    }
+    // lock drops the decrypted keys and the password
+    pub fn lock(&mut self) {
+        self.unlocked = None;
+    }
+    // create creates a new account and stores its keystore
+    pub fn create(&mut self) -> Result<account, WalletError> {
+        let acc = account::new_account()?;
+        match self.add_account(acc.clone()) {
+            Some(e) => Err(e),
+            None => Ok(acc),
+        }
+    }
+    // get_account by address, alias or encoded address; None while the wallet
+    // is locked
+    pub fn get_account<A: ToAddress>(&self, addr: A) -> Option<&dyn Signer> {
+        let addr = addr.to_address(self).ok()?;
+        self.unlocked.as_ref()?.accounts.get_account(addr)
+    }
+    // add_account encrypts acc with the wallet password and stores it
+    pub fn add_account(&mut self, acc: account) -> Option<WalletError> {
+        let addr = acc.address();
+        let unlocked = match self.unlocked.as_mut() {
+            Some(r) => r,
+            None => return Some(WalletError::Locked),
+        };
+        if self.index.contains(&addr) {
+            return Some(WalletError::AccountError(AccountError::AccountExist(addr)));
+        }
+        let keystore = match keystore::encrypt(acc.private_key(), &unlocked.password.0, self.params)
+        {
+            Ok(r) => r,
+            Err(e) => return Some(e.into()),
+        };
+        // the keystore is in place before the index refers to it
+        if let Err(e) = write_atomic(
+            &self.dir.join(keystore_file(addr)),
+            keystore.to_string().as_bytes(),
+        ) {
+            return Some(e.into());
+        }
+        let mut index = self.index.clone();
+        let mut directory = self.directory.clone();
+        index.push(addr);
+        directory.insert(addr);
+        if let Err(e) = write_index(&self.dir, &index, &directory) {
+            return Some(e);
+        }
+        self.index = index;
+        self.directory = directory;
+        unlocked.accounts.add_account(acc);
+        None
+    }
+    // remove_account removes an account and deletes its keystore; the wallet
+    // must be unlocked
+    pub fn remove_account<A: ToAddress>(&mut self, addr: A) -> Option<WalletError> {
+        let addr = match addr.to_address(self) {
+            Ok(r) => r,
+            Err(e) => return Some(e.into()),
+        };
+        let unlocked = match self.unlocked.as_mut() {
+            Some(r) => r,
+            None => return Some(WalletError::Locked),
+        };
+        if !self.index.contains(&addr) {
+            return None;
+        }
+        // the index stops referring to the keystore before it is deleted
+        let index: Vec<AddrV1> = self.index.iter().filter(|a| **a != addr).cloned().collect();
+        if let Err(e) = write_index(&self.dir, &index, &self.directory) {
+            return Some(e);
+        }
+        self.index = index;
+        self.directory.remove(addr);
+        unlocked.accounts.remove_account(addr);
+        match fs::remove_file(self.dir.join(keystore_file(addr))) {
+            Ok(_) => None,
+            Err(e) => Some(e.into()),
+        }
+    }
+    // get_account_by_name by alias or encoded address; None while the wallet
+    // is locked
+    pub fn get_account_by_name(&self, name: &str) -> Option<&dyn Signer> {
+        self.get_account(name)
+    }
+    // metadata returns the alias and labels of an account
+    pub fn metadata<A: ToAddress>(&self, addr: A) -> Option<&Metadata> {
+        self.directory.get(addr.to_address(self).ok()?)
+    }
+    // set_alias sets or, with None, clears the alias of an account
+    pub fn set_alias<A: ToAddress>(
+        &mut self,
+        addr: A,
+        alias: Option<&str>,
+    ) -> Result<(), WalletError> {
+        let addr = addr.to_address(self)?;
+        let mut directory = self.directory.clone();
+        directory.set_alias(addr, alias)?;
+        write_index(&self.dir, &self.index, &directory)?;
+        self.directory = directory;
+        Ok(())
+    }
+    // set_label sets or, with None, clears a label of an account
+    pub fn set_label<A: ToAddress>(
+        &mut self,
+        addr: A,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), WalletError> {
+        let addr = addr.to_address(self)?;
+        let mut directory = self.directory.clone();
+        directory.set_label(addr, key, value)?;
+        write_index(&self.dir, &self.index, &directory)?;
+        self.directory = directory;
+        Ok(())
+    }
+}
+
+impl Resolver for Wallet {
+    fn resolve(&self, name: &str) -> Option<AddrV1> {
+        self.directory.resolve(name)
+    }
+}
+
+fn write_index(dir: &Path, index: &[AddrV1], directory: &Directory) -> Result<(), WalletError> {
+    let index = json!({ "version": INDEX_VERSION, "accounts": directory.to_json(index) });
+    write_atomic(&dir.join(INDEX_FILE), index.to_string().as_bytes())?;
+    Ok(())
+}
+
+fn keystore_file(addr: AddrV1) -> String {
+    format!("{}.json", hex::encode(addr.bytes()))
+}
+
+fn read_keystore(path: &Path) -> Result<Value, WalletError> {
+    match serde_json::from_slice(&fs::read(path)?) {
+        Ok(r) => Ok(r),
+        Err(e) => Err(KeystoreError::InvalidKeystore(e.to_string()).into()),
+    }
+}
+
+fn parse_index(data: &[u8]) -> Result<(Directory, Vec<AddrV1>), WalletError> {
+    let index: Value = match serde_json::from_slice(data) {
+        Ok(r) => r,
+        Err(e) => return Err(WalletError::InvalidIndex(e.to_string())),
+    };
+    match index["version"].as_u64() {
+        Some(v) if (1..=INDEX_VERSION).contains(&v) => (),
+        _ => {
+            return Err(WalletError::InvalidIndex(String::from(
+                "unsupported version",
+            )))
+        }
+    }
+    match Directory::from_json(&index["accounts"]) {
+        Ok(r) => Ok(r),
+        Err(e) => Err(WalletError::InvalidIndex(e)),
+    }
+}
+
+// write_atomic replaces the file at path with data, so that a crash leaves
+// either the old or the new content
+pub(super) fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut file = create_private(&tmp)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)?;
+    if let Some(dir) = path.parent() {
+        // persist the rename; directories cannot be opened on every platform
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+// create_private creates a file that only its owner can read, as keystores
+// should not be readable by other users even though they are encrypted
+#[cfg(unix)]
+fn create_private(path: &Path) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+}
+
+#[cfg(not(unix))]
+fn create_private(path: &Path) -> io::Result<File> {
+    File::create(path)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum WalletError {
+    IoError(String),
+    // InvalidIndex is a malformed wallet index or address book file
+    InvalidIndex(String),
+    KeystoreError(KeystoreError),
+    AccountError(AccountError),
+    Locked,
+}
+
+impl From<io::Error> for WalletError {
+    fn from(e: io::Error) -> Self {
+        WalletError::IoError(e.to_string())
+    }
+}
+
+impl From<KeystoreError> for WalletError {
+    fn from(e: KeystoreError) -> Self {
+        WalletError::KeystoreError(e)
+    }
+}
+
+impl From<AccountError> for WalletError {
+    fn from(e: AccountError) -> Self {
+        WalletError::AccountError(e)
+    }
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WalletError::IoError(e) => write!(f, "io error, {}", e),
+            WalletError::InvalidIndex(e) => write!(f, "invalid wallet index, {}", e),
+            WalletError::KeystoreError(e) => write!(f, "keystore error, {}", e),
+            WalletError::AccountError(e) => write!(f, "account error, {}", e),
+            WalletError::Locked => write!(f, "wallet is locked"),
+        }
+    }
+}
+
+impl error::Error for WalletError {
+    fn description(&self) -> &str {
+        match *self {
+            WalletError::IoError(_) => "io error",
+            WalletError::InvalidIndex(_) => "invalid wallet index",
+            WalletError::KeystoreError(_) => "keystore error",
+            WalletError::AccountError(_) => "account error",
+            WalletError::Locked => "wallet is locked",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PARAMS: KdfParams = KdfParams {
+        log_n: 10,
+        r: 8,
+        p: 1,
+    };
+
+    #[test]
+    fn test_wallet() {
+        let dir = std::env::temp_dir().join(format!("iotex-wallet-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut wallet = Wallet::open(&dir).unwrap();
+        wallet.set_kdf_params(PARAMS);
+        assert!(wallet.is_locked());
+        assert_eq!(wallet.create(), Err(WalletError::Locked));
+        wallet.unlock("password").unwrap();
+        let act1 = wallet.create().unwrap();
+        let act2 = wallet.create().unwrap();
+        assert_eq!(
+            wallet.add_account(act1.clone()),
+            Some(WalletError::AccountError(AccountError::AccountExist(
+                act1.address()
+            )))
+        );
+        assert_eq!(
+            wallet.get_account(act1.address()).unwrap().public_key(),
+            act1.public_key()
+        );
+        wallet.lock();
+        assert!(wallet.get_account(act1.address()).is_none());
+        wallet.set_alias(act2.address(), Some("hot")).unwrap();
+        assert_eq!(
+            wallet.set_alias(act1.address(), Some("hot")),
+            Err(WalletError::AccountError(AccountError::AliasExist(
+                String::from("hot")
+            )))
+        );
+
+        let mut wallet = Wallet::open(&dir).unwrap();
+        assert_eq!(wallet.addresses(), &[act1.address(), act2.address()][..]);
+        assert_eq!(wallet.resolve("hot"), Some(act2.address()));
+        assert_eq!(
+            wallet.unlock("wrong"),
+            Err(WalletError::KeystoreError(KeystoreError::WrongPassword))
+        );
+        assert!(wallet.is_locked());
+        wallet.unlock("password").unwrap();
+        assert_eq!(
+            wallet.get_account_by_name("hot").unwrap().address(),
+            act2.address()
+        );
+        assert_eq!(wallet.remove_account(act1.address()), None);
+        assert!(wallet.get_account(act1.address()).is_none());
+
+        let wallet = Wallet::open(&dir).unwrap();
+        assert_eq!(wallet.addresses(), &[act2.address()][..]);
+        assert!(!dir.join(keystore_file(act1.address())).exists());
+        let keystore = dir.join(format!("{}.json", hex::encode(act2.address().bytes())));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&keystore).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert!(keystore.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use super::*;
+use crate::address::v1::AddrV1;
+
+// WatchOnly is an account whose key we do not hold: it can be queried and can
+// verify signatures, but signing fails with SignerError::WatchOnly
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchOnly {
+    address: AddrV1,
+    public_key: Option<String>,
+}
+
+impl WatchOnly {
+    // new watches addr
+    pub fn new(addr: AddrV1) -> WatchOnly {
+        WatchOnly {
+            address: addr,
+            public_key: None,
+        }
+    }
+    // from_public_key watches the address of a hex encoded public key, which
+    // is kept in uncompressed form
+    pub fn from_public_key(public_key: &str) -> Result<WatchOnly, AccountError> {
+        let public_key = match hex::decode(public_key)
+            .ok()
+            .and_then(|b| secp256k1::PublicKey::from_slice(&b).ok())
+        {
+            Some(r) => hex::encode(&r.serialize_uncompressed()[..]),
+            None => return Err(AccountError::CryptoError(crypto::Error::InvalidPublicKey)),
+        };
+        let hash = match key::public_key_hash(public_key.clone()) {
+            Ok(r) => r,
+            Err(e) => return Err(AccountError::CryptoError(e)),
+        };
+        let addr = match address::from_bytes(&hash.0) {
+            Ok(r) => r,
+            Err(e) => return Err(AccountError::AddressError(e)),
+        };
+        Ok(WatchOnly {
+            address: addr,
+            public_key: Some(public_key),
+        })
+    }
+    pub fn address(&self) -> AddrV1 {
+        self.address
+    }
+    // public_key returns the public key, if it is known
+    pub fn public_key(&self) -> Option<&str> {
+        self.public_key.as_deref()
+    }
+    // verify verifies that sig is a signature of data by this account, with the
+    // public key if it is known and by recovering the signer otherwise
+    pub fn verify(&self, data: &[u8], sig: &[u8]) -> Result<bool, AccountError> {
+        match &self.public_key {
+            Some(public_key) => match key::verify_sig(data, sig, public_key.clone()) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(AccountError::CryptoError(e)),
+            },
+            None => Ok(recover_address(data, sig)? == self.address),
+        }
+    }
+}
+
+impl Signer for WatchOnly {
+    fn address(&self) -> AddrV1 {
+        self.address
+    }
+    // public_key is empty if the public key is not known
+    fn public_key(&self) -> String {
+        self.public_key.clone().unwrap_or_default()
+    }
+    fn sign_digest(&self, _digest: &hash::Hash256b) -> Result<[u8; 65], SignerError> {
+        Err(SignerError::WatchOnly(self.address))
+    }
+    fn watch_only(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use accounts::Accounts;
+
+    // Keyless holds a key but does not tell its public key
+    struct Keyless(account);
+
+    impl Signer for Keyless {
+        fn address(&self) -> AddrV1 {
+            self.0.address()
+        }
+        fn public_key(&self) -> String {
+            String::new()
+        }
+        fn sign_digest(&self, digest: &hash::Hash256b) -> Result<[u8; 65], SignerError> {
+            self.0.sign_digest(digest)
+        }
+    }
+
+    #[test]
+    fn test_watch_only() {
+        let acc = account::new_account().unwrap();
+        let sig = acc.sign(b"IoTeX");
+        let watched = [
+            WatchOnly::new(acc.address()),
+            WatchOnly::from_public_key(&acc.public_key()).unwrap(),
+        ];
+        for w in watched.iter() {
+            assert_eq!(w.address(), acc.address());
+            assert!(w.verify(b"IoTeX", &sig).unwrap());
+            assert!(!w.verify(b"IoTex", &sig).unwrap());
+            assert_eq!(
+                w.sign_digest(&hash::hash256b(b"IoTeX")),
+                Err(SignerError::WatchOnly(acc.address()))
+            );
+        }
+        assert_eq!(watched[1].public_key(), Some(acc.public_key().as_str()));
+        for public_key in ["", "00", &acc.public_key()[2..]].iter() {
+            assert_eq!(
+                WatchOnly::from_public_key(public_key),
+                Err(AccountError::CryptoError(crypto::Error::InvalidPublicKey))
+            );
+        }
+
+        let mut accounts = Accounts::new_accounts();
+        accounts.add_watch_only(watched[0].clone());
+        assert!(accounts.get_account(acc.address()).unwrap().watch_only());
+        assert!(accounts.verify(acc.address(), b"IoTeX", &sig).unwrap());
+        assert!(!accounts.verify(acc.address(), b"IoTex", &sig).unwrap());
+        assert_eq!(
+            accounts.add_account(acc.clone()),
+            Some(AccountError::AccountExist(acc.address()))
+        );
+        let own = accounts.create().unwrap();
+        assert!(!accounts.get_account(own.address()).unwrap().watch_only());
+        assert!(accounts
+            .verify(own.address(), b"IoTeX", &own.sign(b"IoTeX"))
+            .unwrap());
+        // only watch-only accounts are verified by recovering the signer
+        let keyless = account::new_account().unwrap();
+        let sig = keyless.sign(b"IoTeX");
+        accounts.add_signer(Box::new(Keyless(keyless.clone())));
+        assert!(accounts.verify(keyless.address(), b"IoTeX", &sig).is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use super::{
+    account::{recover_address, AccountError, Signer, SignerError},
+    address::v1::AddrV1,
+    crypto::{
+        self,
+        hash::{self, Hash256b},
+    },
+};
+use ethabi::Uint;
+
+mod proto;
+pub mod receipt;
+
+pub use receipt::{Log, Receipt};
+
+// VERSION is the action core version understood by iotex-core
+pub const VERSION: u32 = 1;
+
+// Execution calls or deploys a contract; an empty contract deploys data as bytecode
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Execution {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::decimal"))]
+    pub amount: Uint,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::address_string")
+    )]
+    pub contract: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::hex_bytes"))]
+    pub data: Vec<u8>,
+}
+
+impl Execution {
+    // serialize encodes the execution as an iotextypes.Execution message
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        proto::put_bytes(&mut buf, 1, self.amount.to_string().as_bytes());
+        proto::put_bytes(&mut buf, 2, self.contract.as_bytes());
+        proto::put_bytes(&mut buf, 3, &self.data);
+        buf
+    }
+}
+
+// Envelope is the unsigned action core: gas, nonce and payload
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Envelope {
+    pub version: u32,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::decimal"))]
+    pub gas_price: Uint,
+    pub chain_id: u32,
+    pub execution: Execution,
+}
+
+impl Envelope {
+    // serialize encodes the envelope as an iotextypes.ActionCore message
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        proto::put_uint(&mut buf, 1, u64::from(self.version));
+        proto::put_uint(&mut buf, 2, self.nonce);
+        proto::put_uint(&mut buf, 3, self.gas_limit);
+        proto::put_bytes(&mut buf, 4, self.gas_price.to_string().as_bytes());
+        proto::put_uint(&mut buf, 5, u64::from(self.chain_id));
+        proto::put_message(&mut buf, 12, &self.execution.serialize());
+        buf
+    }
+    // hash returns the hash being signed
+    pub fn hash(&self) -> Hash256b {
+        hash::hash256b(&self.serialize())
+    }
+    // sign seals the envelope with the signer's signature of its hash
+    pub fn sign<S: Signer + ?Sized>(self, signer: &S) -> Result<SealedEnvelope, SignerError> {
+        let sender_pub_key = match hex::decode(signer.public_key()) {
+            Ok(r) => r,
+            Err(_) => return Err(SignerError::CryptoError(crypto::Error::InvalidPublicKey)),
+        };
+        let signature = signer.sign_digest(&self.hash())?;
+        Ok(SealedEnvelope {
+            envelope: self,
+            sender_pub_key,
+            signature,
+        })
+    }
+}
+
+// SealedEnvelope is a signed action ready to be sent to the chain; when
+// deserialized, its sender public key must be the key that signed it
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(rename_all = "camelCase", try_from = "UncheckedSealedEnvelope")
+)]
+pub struct SealedEnvelope {
+    envelope: Envelope,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::hex_bytes"))]
+    sender_pub_key: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::hex_array"))]
+    signature: [u8; 65],
+}
+
+// UncheckedSealedEnvelope is a deserialized SealedEnvelope whose signature has
+// not been checked yet
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UncheckedSealedEnvelope {
+    envelope: Envelope,
+    #[serde(with = "crate::serialization::hex_bytes")]
+    sender_pub_key: Vec<u8>,
+    #[serde(with = "crate::serialization::hex_array")]
+    signature: [u8; 65],
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<UncheckedSealedEnvelope> for SealedEnvelope {
+    type Error = String;
+
+    fn try_from(unchecked: UncheckedSealedEnvelope) -> Result<Self, Self::Error> {
+        use crate::address::Address;
+        let sealed = SealedEnvelope {
+            envelope: unchecked.envelope,
+            sender_pub_key: unchecked.sender_pub_key,
+            signature: unchecked.signature,
+        };
+        let public_key = match secp256k1::PublicKey::from_slice(&sealed.sender_pub_key) {
+            Ok(r) => r,
+            Err(_) => return Err(crypto::Error::InvalidPublicKey.to_string()),
+        };
+        let sender = sealed.sender().map_err(|e| e.to_string())?;
+        if hash::hash160b(&public_key.serialize_uncompressed()[1..]).0 != sender.bytes() {
+            return Err(String::from("sender public key does not match signature"));
+        }
+        Ok(sealed)
+    }
+}
+
+impl SealedEnvelope {
+    // envelope returns the signed action core
+    pub fn envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+    // sender_pub_key returns the uncompressed public key of the signer
+    pub fn sender_pub_key(&self) -> &[u8] {
+        &self.sender_pub_key
+    }
+    // signature returns the 65 bytes recoverable signature
+    pub fn signature(&self) -> &[u8; 65] {
+        &self.signature
+    }
+    // sender recovers the address of the signer from the signature
+    pub fn sender(&self) -> Result<AddrV1, AccountError> {
+        recover_address(&self.envelope.serialize(), &self.signature)
+    }
+    // serialize encodes the sealed envelope as an iotextypes.Action message
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        proto::put_message(&mut buf, 1, &self.envelope.serialize());
+        proto::put_bytes(&mut buf, 2, &self.sender_pub_key);
+        proto::put_bytes(&mut buf, 3, &self.signature);
+        buf
+    }
+    // hash returns the action hash used to look up its receipt
+    pub fn hash(&self) -> Hash256b {
+        hash::hash256b(&self.serialize())
+    }
+}
+
+#[test]
+fn test_sealed_envelope() {
+    let acc = crate::account::account::hex_string_to_account(String::from(
+        "0806c458b262edd333a191e92f561aff338211ee3e18ab315a074a2d82aa343f",
+    ))
+    .unwrap();
+    let envelope = Envelope {
+        version: VERSION,
+        nonce: 1,
+        gas_limit: 100_000,
+        gas_price: Uint::from(1_000_000_000_000u64),
+        chain_id: 0,
+        execution: Execution {
+            amount: Uint::zero(),
+            contract: String::from("io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j"),
+            data: vec![0x12, 0x34],
+        },
+    };
+    // the expected bytes were produced by an independent protobuf, keccak and
+    // RFC 6979 implementation, not by this crate
+    let core = envelope.serialize();
+    assert_eq!(
+        hex::encode(&core),
+        "0801100118a08d06220d31303030303030303030303030\
+         62320a0130\
+         1229696f313837777a703038766e686a6a706b79646e723937716c68386b683064706b6b797466616d386a\
+         1a021234"
+    );
+    assert_eq!(
+        hex::encode(envelope.hash().0),
+        "d1b66fbbabe49465d39d3ea40a3a5cb704ba37f50548cd5592e966043c3295e5"
+    );
+
+    let sealed = envelope.clone().sign(&acc).unwrap();
+    assert_eq!(sealed.envelope(), &envelope);
+    assert_eq!(hex::encode(sealed.sender_pub_key()), acc.public_key());
+    assert_eq!(sealed.sender().unwrap(), acc.address());
+    assert!(crypto::key::verify_sig(&core, sealed.signature(), acc.public_key()).unwrap());
+    assert_eq!(
+        hex::encode(&sealed.signature()[..]),
+        "7e9d556bda26e4eb8ba6fd958ed9acde7c7eec49ece61b2c1f2dbb0591a6ac63\
+         3eae8708b6b5b7877e0c181a03182b85d2d992c787d9da978ff489e3f233b86e01"
+    );
+    assert_eq!(
+        hex::encode(sealed.hash().0),
+        "3867fbbe3b48dd4b7774d136b89b3563971ee2ccbe058fee2b5e67bcf2e8b23a"
+    );
+    assert_eq!(sealed.hash(), hash::hash256b(&sealed.serialize()));
+    assert_ne!(sealed.hash(), envelope.hash());
+}
@@ -0,0 +1,56 @@
+// Minimal protobuf (proto3) wire encoding used to serialize actions exactly as
+// iotex-core does before hashing and signing them.
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_LEN: u64 = 2;
+
+fn put_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+fn put_key(buf: &mut Vec<u8>, field: u32, wire: u64) {
+    put_varint(buf, (u64::from(field) << 3) | wire);
+}
+
+// put_uint writes a varint field, omitting the proto3 default value
+pub fn put_uint(buf: &mut Vec<u8>, field: u32, v: u64) {
+    if v == 0 {
+        return;
+    }
+    put_key(buf, field, WIRE_VARINT);
+    put_varint(buf, v);
+}
+
+// put_bytes writes a bytes or string field, omitting the proto3 default value
+pub fn put_bytes(buf: &mut Vec<u8>, field: u32, v: &[u8]) {
+    if v.is_empty() {
+        return;
+    }
+    put_key(buf, field, WIRE_LEN);
+    put_varint(buf, v.len() as u64);
+    buf.extend_from_slice(v);
+}
+
+// put_message writes an embedded message field, even when it is empty
+pub fn put_message(buf: &mut Vec<u8>, field: u32, v: &[u8]) {
+    put_key(buf, field, WIRE_LEN);
+    put_varint(buf, v.len() as u64);
+    buf.extend_from_slice(v);
+}
+
+#[test]
+fn test_encode() {
+    let mut buf = Vec::new();
+    put_uint(&mut buf, 1, 0);
+    put_bytes(&mut buf, 2, b"");
+    assert!(buf.is_empty());
+
+    put_uint(&mut buf, 1, 300);
+    put_bytes(&mut buf, 2, b"io");
+    put_message(&mut buf, 12, b"");
+    assert_eq!(hex::encode(&buf), "08ac021202696f6200");
+}
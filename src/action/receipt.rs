@@ -0,0 +1,47 @@
+use crate::crypto::hash::Hash256b;
+
+// RECEIPT_STATUS_SUCCESS is the status of a receipt whose action succeeded
+pub const RECEIPT_STATUS_SUCCESS: u64 = 1;
+
+// Log is an event emitted by a contract during an execution
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Log {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::address_string")
+    )]
+    pub contract_address: String,
+    pub topics: Vec<Hash256b>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::hex_bytes"))]
+    pub data: Vec<u8>,
+    pub blk_height: u64,
+    pub act_hash: Hash256b,
+    pub index: u32,
+}
+
+// Receipt is the result of an action once it is included in a block
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Receipt {
+    pub status: u64,
+    pub blk_height: u64,
+    pub act_hash: Hash256b,
+    pub gas_consumed: u64,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::address_string")
+    )]
+    pub contract_address: String,
+    pub logs: Vec<Log>,
+    pub execution_revert_msg: String,
+}
+
+impl Receipt {
+    // succeeded tells whether the action was executed successfully
+    pub fn succeeded(&self) -> bool {
+        self.status == RECEIPT_STATUS_SUCCESS
+    }
+}
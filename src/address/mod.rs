@@ -6,9 +6,6 @@ const MAINNET_PREFIX: &str = "io";
 // TESTNET_PREFIX is the prefix added to the human readable address of testnet
 const TESTNET_PREFIX: &str = "it";
 
-// ZERO_ADDRESS is the IoTeX address whose hash160 is all zero
-const ZERO_ADDRESS: &str = "io1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqd39ym7";
-
 static mut IS_TEST_NET: bool = false;
 
 pub fn set_network(is_test_net: bool) {
@@ -30,12 +27,15 @@ pub fn from_bytes(bytes: &[u8]) -> Result<v1::AddrV1, AddrError> {
     v1::_V1.from_bytes(bytes)
 }
 
+// is_test_net tells whether set_network selected testnet
+pub fn is_test_net() -> bool {
+    unsafe { IS_TEST_NET }
+}
+
 fn prefix() -> &'static str {
     let mut prefix = MAINNET_PREFIX;
-    unsafe {
-        if IS_TEST_NET {
-            prefix = TESTNET_PREFIX;
-        }
+    if is_test_net() {
+        prefix = TESTNET_PREFIX;
     }
     prefix
 }
@@ -50,7 +50,7 @@ pub enum AddrError {
 impl fmt::Display for AddrError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            AddrError::BechError(e) => write!(f, "bech32 error, {})", e.description()),
+            AddrError::BechError(e) => write!(f, "bech32 error, {})", e),
             AddrError::InvalidAddrLen(n) => write!(f, "invalid address length ({})", n),
             AddrError::AddrPrefixNotMatch => write!(f, "address's prefix doesn't match"),
         }
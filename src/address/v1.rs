@@ -1,10 +1,46 @@
 use super::*;
 use crate::crypto::hash;
 use bech32::{self, ToBase32};
+use std::convert::TryFrom;
+use std::{fmt, str};
 
 // _V1 is a singleton and defines V1 address metadata
 pub const _V1: V1 = V1 { address_length: 20 };
 
+// ZERO_ADDRESS is the address whose hash160 is all zero
+pub const ZERO_ADDRESS: AddrV1 = AddrV1 {
+    payload: hash::Hash160b([0; 20]),
+};
+
+// IDs of the system protocols, whose actions are sent to the address hashed
+// from the ID as iotex-core does
+pub const STAKING_PROTOCOL_ID: &str = "staking";
+pub const REWARDING_PROTOCOL_ID: &str = "rewarding";
+pub const POLL_PROTOCOL_ID: &str = "poll";
+
+// protocol_address returns the address of the system protocol with the given
+// ID, the hash160 of the ID
+pub fn protocol_address(protocol_id: &str) -> AddrV1 {
+    AddrV1 {
+        payload: hash::hash160b(protocol_id.as_bytes()),
+    }
+}
+
+// staking_protocol_address returns the address of native staking actions
+pub fn staking_protocol_address() -> AddrV1 {
+    protocol_address(STAKING_PROTOCOL_ID)
+}
+
+// rewarding_protocol_address returns the address of reward deposits and claims
+pub fn rewarding_protocol_address() -> AddrV1 {
+    protocol_address(REWARDING_PROTOCOL_ID)
+}
+
+// poll_protocol_address returns the address of the delegate poll protocol
+pub fn poll_protocol_address() -> AddrV1 {
+    protocol_address(POLL_PROTOCOL_ID)
+}
+
 pub struct V1 {
     pub address_length: usize,
 }
@@ -12,10 +48,7 @@ pub struct V1 {
 impl V1 {
     // from_string decodes an encoded address string into an address struct
     pub fn from_string(&self, encoded_addr: &str) -> Result<AddrV1, AddrError> {
-        let payload = match self.decode_bech32(encoded_addr) {
-            Ok(r) => r,
-            Err(e) => return Err(e),
-        };
+        let payload = self.decode_bech32(encoded_addr)?;
         self.from_bytes(&payload[..])
     }
     // from_bytes converts a byte array into an address struct
@@ -43,11 +76,38 @@ impl V1 {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct AddrV1 {
     payload: hash::Hash160b,
 }
 
+// AddrV1 displays and parses as its encoded string
+impl fmt::Display for AddrV1 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.string())
+    }
+}
+
+impl str::FromStr for AddrV1 {
+    type Err = AddrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        _V1.from_string(s)
+    }
+}
+
+impl AsRef<[u8]> for AddrV1 {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes()
+    }
+}
+
+impl TryFrom<&[u8]> for AddrV1 {
+    type Error = AddrError;
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        _V1.from_bytes(bytes)
+    }
+}
+
 impl Address for AddrV1 {
     // TODO: fix string()'s wrong output
     fn string(&self) -> String {
@@ -66,15 +126,15 @@ fn test_address() {
     set_network(false);
     let bytes = match hex::decode("3f9c20bcec9de520d88d98cbe07ee7b5ded0dac4") {
         Ok(r) => r,
-        Err(e) => panic!(e),
+        Err(e) => panic!("{:?}", e),
     };
     let addr1 = match _V1.from_bytes(&bytes[..]) {
         Ok(r) => r,
-        Err(e) => panic!(e),
+        Err(e) => panic!("{:?}", e),
     };
     let addr2 = match _V1.from_string("io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j") {
         Ok(r) => r,
-        Err(e) => panic!(e),
+        Err(e) => panic!("{:?}", e),
     };
     assert_eq!(addr1.bytes(), addr2.bytes());
     assert_eq!(
@@ -84,5 +144,40 @@ fn test_address() {
     assert_eq!(
         addr1.string(),
         String::from("io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j")
-    )
+    );
+
+    let addr3: AddrV1 = "io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j".parse().unwrap();
+    assert_eq!(addr3, addr1);
+    assert_eq!(addr3.to_string(), addr1.string());
+    assert_eq!(AddrV1::try_from(addr3.as_ref()), Ok(addr1));
+    assert_eq!(
+        AddrV1::try_from(&bytes[1..]),
+        Err(AddrError::InvalidAddrLen(19))
+    );
+    assert!("io1invalid".parse::<AddrV1>().is_err());
+    let set: std::collections::BTreeSet<AddrV1> = vec![addr1, addr2, addr3].into_iter().collect();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn test_protocol_address() {
+    set_network(false);
+    assert_eq!(
+        ZERO_ADDRESS.string(),
+        "io1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqd39ym7"
+    );
+    assert_eq!(ZERO_ADDRESS.bytes(), &[0u8; 20][..]);
+    // values of iotex-core
+    assert_eq!(
+        staking_protocol_address().string(),
+        "io1qnpz47hx5q6r3w876axtrn6yz95d70cjl35r53"
+    );
+    assert_eq!(
+        rewarding_protocol_address().string(),
+        "io154mvzs09vkgn0hw6gg3ayzw5w39jzp47f8py9v"
+    );
+    assert_eq!(
+        poll_protocol_address().string(),
+        "io1ze4hg0pvrftuj0pw90p7z6wj3wae7mdrwws6d5"
+    );
 }
@@ -0,0 +1,67 @@
+// Parameters of the IoTeX networks, as set in the iotex-core genesis
+use crate::address;
+use std::time::Duration;
+
+// chain IDs signed into actions
+pub const MAINNET_CHAIN_ID: u32 = 1;
+pub const TESTNET_CHAIN_ID: u32 = 2;
+
+// EVM network IDs returned by the Web3 endpoints (eth_chainId)
+pub const MAINNET_EVM_NETWORK_ID: u64 = 4689;
+pub const TESTNET_EVM_NETWORK_ID: u64 = 4690;
+
+// IOTX_DECIMALS is the number of decimals of IOTX: 1 IOTX is 10^18 Rau
+pub const IOTX_DECIMALS: u8 = 18;
+
+// NUM_DELEGATES is the number of delegates producing blocks in an epoch
+pub const NUM_DELEGATES: u64 = 24;
+// NUM_SUB_EPOCHS is the number of blocks each delegate produces in an epoch
+pub const NUM_SUB_EPOCHS: u64 = 15;
+// BLOCKS_PER_EPOCH is the length of an epoch in blocks
+pub const BLOCKS_PER_EPOCH: u64 = NUM_DELEGATES * NUM_SUB_EPOCHS;
+// BLOCK_INTERVAL is the time between two blocks
+pub const BLOCK_INTERVAL: Duration = Duration::from_secs(5);
+
+// GENESIS_BLOCK_GAS_LIMIT is the genesis gas limit of a block
+pub const GENESIS_BLOCK_GAS_LIMIT: u64 = 20_000_000;
+// GENESIS_ACTION_GAS_LIMIT is the genesis gas limit of a single action
+pub const GENESIS_ACTION_GAS_LIMIT: u64 = 5_000_000;
+
+// chain_id returns the chain ID of the network selected by address::set_network
+pub fn chain_id() -> u32 {
+    if address::is_test_net() {
+        TESTNET_CHAIN_ID
+    } else {
+        MAINNET_CHAIN_ID
+    }
+}
+
+// evm_network_id returns the EVM network ID of the network selected by
+// address::set_network
+pub fn evm_network_id() -> u64 {
+    if address::is_test_net() {
+        TESTNET_EVM_NETWORK_ID
+    } else {
+        MAINNET_EVM_NETWORK_ID
+    }
+}
+
+#[test]
+fn test_chain() {
+    // values of the iotex-core mainnet genesis
+    assert_eq!(chain_id(), 1);
+    assert_eq!(evm_network_id(), 4689);
+    assert_eq!(TESTNET_CHAIN_ID, 2);
+    assert_eq!(TESTNET_EVM_NETWORK_ID, 4690);
+    assert_eq!(BLOCKS_PER_EPOCH, 360);
+    assert_eq!(BLOCK_INTERVAL.as_secs(), 5);
+    assert_eq!(GENESIS_BLOCK_GAS_LIMIT, 20_000_000);
+    assert_eq!(GENESIS_ACTION_GAS_LIMIT, 5_000_000);
+    assert_eq!(
+        crate::token::format_units(
+            ethabi::Uint::exp10(usize::from(IOTX_DECIMALS)),
+            IOTX_DECIMALS
+        ),
+        "1"
+    );
+}
@@ -0,0 +1,111 @@
+use super::*;
+use crate::address::v1::AddrV1;
+use crate::crypto::hash::{self, Hash256b};
+
+// deployment_address returns the address of the contract deployed by sender
+// with the deployment action of the given nonce. iotex-core hands the EVM the
+// pending nonce for accounts created with zero nonce, but the pending nonce
+// minus one for legacy accounts, whose first action has nonce 1; legacy tells
+// which kind sender is.
+pub fn deployment_address(sender: &AddrV1, action_nonce: u64, legacy: bool) -> AddrV1 {
+    if legacy {
+        create_address(sender, action_nonce.saturating_sub(1))
+    } else {
+        create_address(sender, action_nonce)
+    }
+}
+
+// create_address returns the address of the contract created by sender when
+// its EVM nonce is nonce: keccak256(rlp([sender, nonce]))[12:], as in
+// go-ethereum, which iotex-core runs deployments through. See
+// deployment_address for the nonce of a deployment action.
+pub fn create_address(sender: &AddrV1, nonce: u64) -> AddrV1 {
+    let nonce_bytes = nonce.to_be_bytes();
+    let nonce_bytes = &nonce_bytes[nonce_bytes.iter().take_while(|b| **b == 0).count()..];
+    let mut rlp = vec![0, 0x80 + 20];
+    rlp.extend_from_slice(sender.bytes());
+    match nonce_bytes {
+        // a single byte below 0x80 is its own encoding; zero is the empty string
+        [b] if *b < 0x80 => rlp.push(*b),
+        _ => {
+            rlp.push(0x80 + nonce_bytes.len() as u8);
+            rlp.extend_from_slice(nonce_bytes);
+        }
+    }
+    // the payload is at most 30 bytes, so the list prefix is a single byte
+    rlp[0] = 0xc0 + (rlp.len() - 1) as u8;
+    contract_address(&hash::hash256b(&rlp))
+}
+
+// create2_address returns the address of the contract deployed by the
+// CREATE2 opcode of deployer: keccak256(0xff ++ deployer ++ salt ++
+// keccak256(init_code))[12:]
+pub fn create2_address(deployer: &AddrV1, salt: &[u8; 32], init_code_hash: &Hash256b) -> AddrV1 {
+    let mut data = Vec::with_capacity(85);
+    data.push(0xff);
+    data.extend_from_slice(deployer.bytes());
+    data.extend_from_slice(salt);
+    data.extend_from_slice(&init_code_hash.0);
+    contract_address(&hash::hash256b(&data))
+}
+
+fn contract_address(hash: &Hash256b) -> AddrV1 {
+    address::from_bytes(&hash.0[12..]).expect("20 bytes address")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn addr(h: &str) -> AddrV1 {
+        address::from_bytes(&hex::decode(h).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_create_address() {
+        // vectors of the Ethereum CREATE and EIP-1014 CREATE2 derivations
+        let sender = addr("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+        let tests = [
+            (0, "cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"),
+            (1, "343c43a37d37dff08ae8c4a11544c718abb4fcf8"),
+            (2, "f778b86fa74e846c4f0a1fbd1335fe81c00a0c91"),
+            (3, "fffd933a0bc612844eaf0c6fe3e5b8e9b6c1d19c"),
+        ];
+        for (nonce, expected) in tests.iter() {
+            assert_eq!(create_address(&sender, *nonce), addr(expected));
+        }
+        // a legacy account deploys with the nonce before its action's
+        assert_eq!(
+            deployment_address(&sender, 1, true),
+            addr("cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d")
+        );
+        assert_eq!(
+            deployment_address(&sender, 1, false),
+            addr("343c43a37d37dff08ae8c4a11544c718abb4fcf8")
+        );
+
+        let code_hash = hash::hash256b(&[0x00]);
+        assert_eq!(
+            create2_address(&addr(&"00".repeat(20)), &[0; 32], &code_hash),
+            addr("4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38")
+        );
+        assert_eq!(
+            create2_address(
+                &addr("deadbeef00000000000000000000000000000000"),
+                &[0; 32],
+                &code_hash
+            ),
+            addr("b928f69bb1d91cd65274e3c79d8986362984fda3")
+        );
+        let mut salt = [0u8; 32];
+        salt[28..].copy_from_slice(&[0xca, 0xfe, 0xba, 0xbe]);
+        assert_eq!(
+            create2_address(
+                &addr("00000000000000000000000000000000deadbeef"),
+                &salt,
+                &hash::hash256b(&hex::decode("deadbeef").unwrap())
+            ),
+            addr("60f3f640a8508fc6a86d45df051962668e1e8ac7")
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use super::*;
+use crate::action::{Log, Receipt};
+use ethabi::{ParamType, RawTopicFilter, Topic, TopicFilter};
+
+// Event is a receipt log decoded against the contract ABI
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub name: String,
+    pub params: Vec<ethabi::LogParam>,
+    pub index: u32,
+}
+
+impl Event {
+    // param returns the value of the parameter called name
+    pub fn param(&self, name: &str) -> Option<&Token> {
+        self.params
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| &p.value)
+    }
+}
+
+impl<C: Client> Contract<C> {
+    // decode_logs decodes the logs of receipt emitted by this contract, skipping
+    // logs of other contracts and events missing from the ABI
+    pub fn decode_logs(&self, receipt: &Receipt) -> Result<Vec<Event>, ContractError> {
+        let mut events = Vec::new();
+        for log in receipt.logs.iter() {
+            if let Some(event) = self.decode_log(log)? {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+    // decode_log decodes a single log, returning None if it is not an ABI event
+    // of this contract
+    pub fn decode_log(&self, log: &Log) -> Result<Option<Event>, ContractError> {
+        if log.contract_address != self.address.string() {
+            return Ok(None);
+        }
+        let topic0 = match log.topics.first() {
+            Some(t) => ethabi::Hash::from_slice(&t.0),
+            None => return Ok(None),
+        };
+        let event = match self
+            .abi
+            .events()
+            .find(|e| !e.anonymous && e.signature() == topic0)
+        {
+            Some(r) => r,
+            None => return Ok(None),
+        };
+        match parse_log(event, log) {
+            Ok(params) => Ok(Some(Event {
+                name: event.name.clone(),
+                params,
+                index: log.index,
+            })),
+            Err(e) => Err(ContractError::AbiError(e)),
+        }
+    }
+    // topic_filter builds the topics matching event name whose indexed
+    // arguments equal indexed, in declaration order; missing ones match any value
+    pub fn topic_filter(
+        &self,
+        name: &str,
+        indexed: Vec<Topic<Token>>,
+    ) -> Result<TopicFilter, ContractError> {
+        let event = match self.abi.event(name) {
+            Ok(r) => r,
+            Err(e) => return Err(ContractError::AbiError(e)),
+        };
+        if indexed.len() > 3 {
+            return Err(ContractError::AbiError(
+                ethabi::ErrorKind::InvalidData.into(),
+            ));
+        }
+        let mut topics = indexed.into_iter();
+        let raw = RawTopicFilter {
+            topic0: topics.next().unwrap_or_default(),
+            topic1: topics.next().unwrap_or_default(),
+            topic2: topics.next().unwrap_or_default(),
+        };
+        match event.filter(raw) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(ContractError::AbiError(e)),
+        }
+    }
+}
+
+// parse_log decodes the parameters of a non-anonymous event by position;
+// ethabi's Event::parse_log matches them by name, which mixes up unnamed or
+// same-named parameters. Indexed strings, bytes and arrays are only present as
+// their hash, which is returned as bytes32.
+fn parse_log(event: &ethabi::Event, log: &Log) -> ethabi::Result<Vec<ethabi::LogParam>> {
+    let topic_types: Vec<ParamType> = event
+        .inputs
+        .iter()
+        .filter(|p| p.indexed)
+        .map(|p| match p.kind {
+            ParamType::String
+            | ParamType::Bytes
+            | ParamType::Array(_)
+            | ParamType::FixedArray(..) => ParamType::FixedBytes(32),
+            ref kind => kind.clone(),
+        })
+        .collect();
+    if log.topics.len() != topic_types.len() + 1 {
+        return Err(ethabi::ErrorKind::InvalidData.into());
+    }
+    let topics: Vec<u8> = log.topics[1..].iter().flat_map(|t| t.0.to_vec()).collect();
+    let mut topic_tokens = ethabi::decode(&topic_types, &topics)?.into_iter();
+    let data_types: Vec<ParamType> = event
+        .inputs
+        .iter()
+        .filter(|p| !p.indexed)
+        .map(|p| p.kind.clone())
+        .collect();
+    let mut data_tokens = ethabi::decode(&data_types, &log.data)?.into_iter();
+    let params = event
+        .inputs
+        .iter()
+        .map(|p| {
+            let value = if p.indexed {
+                topic_tokens.next()
+            } else {
+                data_tokens.next()
+            };
+            ethabi::LogParam {
+                name: p.name.clone(),
+                value: value.expect("one token per input"),
+            }
+        })
+        .collect();
+    Ok(params)
+}
+
+// matches tells whether the topics of log satisfy filter
+pub fn matches(filter: &TopicFilter, log: &Log) -> bool {
+    let topics = [
+        &filter.topic0,
+        &filter.topic1,
+        &filter.topic2,
+        &filter.topic3,
+    ];
+    topics.iter().enumerate().all(|(i, topic)| {
+        let actual = log.topics.get(i).map(|t| ethabi::Hash::from_slice(&t.0));
+        match (topic, actual) {
+            (Topic::Any, _) => true,
+            (Topic::This(h), Some(a)) => *h == a,
+            (Topic::OneOf(hs), Some(a)) => hs.contains(&a),
+            (_, None) => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::crypto::hash::{self, Hash256b};
+    use crate::rpc::mock::{self, MockClient};
+
+    const ABI: &str = r#"[
+        {"type":"event","name":"Transfer","anonymous":false,
+         "inputs":[{"name":"from","type":"address","indexed":true},
+                   {"name":"to","type":"address","indexed":true},
+                   {"name":"value","type":"uint256","indexed":false}]}
+    ]"#;
+    const CONTRACT: &str = "io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j";
+
+    fn topic(addr: &ethabi::Address) -> Hash256b {
+        hash::bytes_to_hash256(addr.as_bytes())
+    }
+
+    fn transfer_log(from: &ethabi::Address, to: &ethabi::Address, value: u64) -> Log {
+        Log {
+            contract_address: String::from(CONTRACT),
+            topics: vec![
+                hash::hash256b(b"Transfer(address,address,uint256)"),
+                topic(from),
+                topic(to),
+            ],
+            data: ethabi::encode(&[Token::Uint(Uint::from(value))]),
+            blk_height: 1,
+            act_hash: Hash256b([0; 32]),
+            index: 2,
+        }
+    }
+
+    #[test]
+    fn test_decode_logs() {
+        let client = MockClient::new();
+        let addr = address::from_string(CONTRACT).unwrap();
+        let acc = crate::account::account::new_account().unwrap();
+        let c = Contract::new(addr, ABI, &client, acc).unwrap();
+        let from = ethabi::Address::from_low_u64_be(1);
+        let to = ethabi::Address::from_low_u64_be(2);
+
+        let mut receipt = mock::success_receipt();
+        let mut foreign = transfer_log(&from, &to, 5);
+        foreign.contract_address = String::from("io1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqd39ym7");
+        let mut unknown = transfer_log(&from, &to, 5);
+        unknown.topics[0] = hash::hash256b(b"Approval(address,address,uint256)");
+        receipt.logs = vec![foreign, transfer_log(&from, &to, 5), unknown];
+
+        let events = c.decode_logs(&receipt).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "Transfer");
+        assert_eq!(events[0].index, 2);
+        assert_eq!(events[0].param("from"), Some(&Token::Address(from)));
+        assert_eq!(events[0].param("to"), Some(&Token::Address(to)));
+        assert_eq!(events[0].param("value"), Some(&Token::Uint(Uint::from(5))));
+
+        let filter = c
+            .topic_filter(
+                "Transfer",
+                vec![Topic::Any, Topic::This(Token::Address(to))],
+            )
+            .unwrap();
+        assert!(matches(&filter, &transfer_log(&from, &to, 5)));
+        assert!(!matches(&filter, &transfer_log(&to, &from, 5)));
+        assert!(c.topic_filter("Approval", vec![]).is_err());
+        assert!(c
+            .topic_filter("Transfer", vec![Topic::This(Token::Bool(true))])
+            .is_err());
+    }
+
+    #[test]
+    fn test_decode_unnamed_params() {
+        // parameters without names are decoded by position
+        const ABI: &str = r#"[
+            {"type":"event","name":"Pair","anonymous":false,
+             "inputs":[{"name":"","type":"uint256","indexed":true},
+                       {"name":"","type":"uint256","indexed":false},
+                       {"name":"","type":"uint256","indexed":false}]}
+        ]"#;
+        let client = MockClient::new();
+        let addr = address::from_string(CONTRACT).unwrap();
+        let acc = crate::account::account::new_account().unwrap();
+        let c = Contract::new(addr, ABI, &client, acc).unwrap();
+        let log = Log {
+            contract_address: String::from(CONTRACT),
+            topics: vec![
+                hash::hash256b(b"Pair(uint256,uint256,uint256)"),
+                hash::bytes_to_hash256(&ethabi::encode(&[Token::Uint(Uint::from(1))])),
+            ],
+            data: ethabi::encode(&[Token::Uint(Uint::from(2)), Token::Uint(Uint::from(3))]),
+            blk_height: 1,
+            act_hash: Hash256b([0; 32]),
+            index: 0,
+        };
+        let event = c.decode_log(&log).unwrap().unwrap();
+        let values: Vec<Token> = event.params.into_iter().map(|p| p.value).collect();
+        assert_eq!(
+            values,
+            vec![
+                Token::Uint(Uint::from(1)),
+                Token::Uint(Uint::from(2)),
+                Token::Uint(Uint::from(3))
+            ]
+        );
+        let mut short = log;
+        short.topics.pop();
+        assert!(c.decode_log(&short).is_err());
+    }
+}
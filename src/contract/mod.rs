@@ -1,20 +1,598 @@
 use super::{
-    account, address,
-    crypto::{self, key},
+    account::{
+        address_book::{Directory, Resolver, ToAddress},
+        AccountError, Signer, SignerError,
+    },
+    action::{Envelope, Execution, VERSION},
+    address::{self, Address},
+    crypto::{self, hash::Hash256b},
+    rpc::{self, Client, PendingReceipt},
 };
-use ethabi;
+use ethabi::{self, Token, Uint};
+use std::{error, fmt, time::Duration};
 
-#[derive(Copy, Clone, Debug)]
+pub mod create;
+pub mod event;
+pub mod multicall;
+pub mod packed;
+pub mod revert;
+
+pub use create::{create2_address, create_address, deployment_address};
+pub use event::Event;
+pub use multicall::{Call, Multicall};
+pub use packed::{encode_packed, hash_packed};
+pub use revert::{CustomError, Revert};
+
+// DEFAULT_GAS_LIMIT is the gas limit of executions unless set otherwise
+pub const DEFAULT_GAS_LIMIT: u64 = 1_000_000;
+// DEFAULT_GAS_PRICE is the gas price (in Rau) of executions unless set otherwise
+pub const DEFAULT_GAS_PRICE: u64 = 1_000_000_000_000;
+// DEFAULT_RECEIPT_ATTEMPTS is how many times a deployment receipt is polled
+pub const DEFAULT_RECEIPT_ATTEMPTS: usize = 12;
+// DEFAULT_RECEIPT_INTERVAL is the delay between two polls of a deployment receipt
+pub const DEFAULT_RECEIPT_INTERVAL: Duration = Duration::from_secs(5);
+
+// Data is an ABI encoded method call
+#[derive(Clone, Debug, PartialEq)]
 pub struct Data {
-    method: string,
-    abi: ethabi::Contract,
-    Raw: Vec<u8>,
+    method: String,
+    raw: Vec<u8>,
+}
+
+impl Data {
+    // method returns the name of the called method
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+    // raw returns the selector followed by the encoded arguments
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+}
+
+// DeployOptions tunes a deployment and how long to wait for its receipt
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeployOptions {
+    pub amount: Uint,
+    pub gas_limit: u64,
+    pub gas_price: Uint,
+    pub chain_id: u32,
+    pub receipt_attempts: usize,
+    pub receipt_interval: Duration,
 }
 
-#[derive(Copy, Clone, Debug)]
-pub struct Contract {
+impl Default for DeployOptions {
+    fn default() -> Self {
+        DeployOptions {
+            amount: Uint::zero(),
+            gas_limit: DEFAULT_GAS_LIMIT,
+            gas_price: Uint::from(DEFAULT_GAS_PRICE),
+            chain_id: crate::chain::chain_id(),
+            receipt_attempts: DEFAULT_RECEIPT_ATTEMPTS,
+            receipt_interval: DEFAULT_RECEIPT_INTERVAL,
+        }
+    }
+}
+
+pub struct Contract<C: Client> {
     address: address::v1::AddrV1,
     abi: ethabi::Contract,
-    // api:
-    account: account::account,
+    errors: Vec<CustomError>,
+    client: C,
+    signer: Box<dyn Signer>,
+    resolver: Box<dyn Resolver>,
+    gas_limit: u64,
+    gas_price: Uint,
+    chain_id: u32,
+}
+
+impl<C: Client> Contract<C> {
+    // new binds the ABI to the contract deployed at address; calls are sent by signer
+    pub fn new<S: Signer + 'static>(
+        address: address::v1::AddrV1,
+        abi_json: &str,
+        client: C,
+        signer: S,
+    ) -> Result<Contract<C>, ContractError> {
+        let (abi, errors) = revert::load_abi(abi_json)?;
+        Ok(Contract {
+            address,
+            abi,
+            errors,
+            client,
+            signer: Box::new(signer),
+            resolver: Box::new(Directory::new()),
+            gas_limit: DEFAULT_GAS_LIMIT,
+            gas_price: Uint::from(DEFAULT_GAS_PRICE),
+            chain_id: crate::chain::chain_id(),
+        })
+    }
+    // deploy deploys bytecode with the encoded constructor params, waits for the
+    // receipt and returns the contract bound to the deployed address
+    pub fn deploy<S: Signer + 'static>(
+        client: C,
+        signer: S,
+        abi_json: &str,
+        bytecode: &[u8],
+        params: &[Token],
+        options: &DeployOptions,
+    ) -> Result<Contract<C>, ContractError> {
+        let (abi, errors) = revert::load_abi(abi_json)?;
+        let data = match (abi.constructor(), params.is_empty()) {
+            (Some(constructor), _) => match constructor.encode_input(bytecode.to_vec(), params) {
+                Ok(r) => r,
+                Err(e) => return Err(ContractError::AbiError(e)),
+            },
+            (None, true) => bytecode.to_vec(),
+            (None, false) => {
+                return Err(ContractError::AbiError(
+                    ethabi::ErrorKind::InvalidData.into(),
+                ))
+            }
+        };
+        let execution = Execution {
+            amount: options.amount,
+            contract: String::new(),
+            data,
+        };
+        let hash = send_execution(
+            &client,
+            &signer,
+            execution,
+            options.gas_limit,
+            options.gas_price,
+            options.chain_id,
+        )?;
+        let receipt = PendingReceipt::new(&client, hash)
+            .wait(options.receipt_attempts, options.receipt_interval)?;
+        revert::check_receipt(&errors, &receipt, &[])?;
+        let address = match address::from_string(&receipt.contract_address) {
+            Ok(r) => r,
+            Err(e) => return Err(ContractError::AddressError(e)),
+        };
+        Ok(Contract {
+            address,
+            abi,
+            errors,
+            client,
+            signer: Box::new(signer),
+            resolver: Box::new(Directory::new()),
+            gas_limit: options.gas_limit,
+            gas_price: options.gas_price,
+            chain_id: options.chain_id,
+        })
+    }
+    // address returns the contract address
+    pub fn address(&self) -> address::v1::AddrV1 {
+        self.address
+    }
+    // abi returns the contract ABI
+    pub fn abi(&self) -> &ethabi::Contract {
+        &self.abi
+    }
+    // client returns the client the contract talks to
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+    // signer returns the signer sending calls and executions
+    pub fn signer(&self) -> &dyn Signer {
+        self.signer.as_ref()
+    }
+    // set_resolver sets what turns aliases given as address arguments into
+    // addresses; by default only encoded addresses are understood
+    pub fn set_resolver<R: Resolver + 'static>(&mut self, resolver: R) {
+        self.resolver = Box::new(resolver);
+    }
+    // resolve turns an address, alias or encoded address into an address
+    pub fn resolve<A: ToAddress>(&self, addr: A) -> Result<address::v1::AddrV1, ContractError> {
+        Ok(addr.to_address(self.resolver.as_ref())?)
+    }
+    // address_token resolves addr into an ABI address argument
+    pub fn address_token<A: ToAddress>(&self, addr: A) -> Result<Token, ContractError> {
+        Ok(address_to_token(&self.resolve(addr)?))
+    }
+    // set_gas_limit sets the gas limit of subsequent calls and executions
+    pub fn set_gas_limit(&mut self, gas_limit: u64) {
+        self.gas_limit = gas_limit;
+    }
+    // set_gas_price sets the gas price (in Rau) of subsequent executions
+    pub fn set_gas_price(&mut self, gas_price: Uint) {
+        self.gas_price = gas_price;
+    }
+    // set_chain_id sets the chain ID executions are signed for
+    pub fn set_chain_id(&mut self, chain_id: u32) {
+        self.chain_id = chain_id;
+    }
+    // encode encodes a call of method with params
+    pub fn encode(&self, method: &str, params: &[Token]) -> Result<Data, ContractError> {
+        let function = self.function(method)?;
+        match function.encode_input(params) {
+            Ok(raw) => Ok(Data {
+                method: String::from(method),
+                raw,
+            }),
+            Err(e) => Err(ContractError::AbiError(e)),
+        }
+    }
+    // call reads method through ReadContract and decodes its outputs
+    pub fn call(&self, method: &str, params: &[Token]) -> Result<Vec<Token>, ContractError> {
+        let data = self.encode(method, params)?;
+        let execution = self.execution(data.raw, Uint::zero());
+        let res = self
+            .client
+            .read_contract(&execution, &self.signer.address(), self.gas_limit)?;
+        revert::check_receipt(&self.errors, &res.receipt, &res.data)?;
+        match self.function(method)?.decode_output(&res.data) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(ContractError::AbiError(e)),
+        }
+    }
+    // execute signs and sends an Execution of method, transferring amount (in Rau)
+    pub fn execute(
+        &self,
+        method: &str,
+        params: &[Token],
+        amount: Uint,
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        let data = self.encode(method, params)?;
+        let execution = self.execution(data.raw, amount);
+        let hash = send_execution(
+            &self.client,
+            self.signer.as_ref(),
+            execution,
+            self.gas_limit,
+            self.gas_price,
+            self.chain_id,
+        )?;
+        Ok(PendingReceipt::new(&self.client, hash))
+    }
+
+    fn function(&self, method: &str) -> Result<&ethabi::Function, ContractError> {
+        match self.abi.function(method) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(ContractError::AbiError(e)),
+        }
+    }
+
+    fn execution(&self, data: Vec<u8>, amount: Uint) -> Execution {
+        Execution {
+            amount,
+            contract: self.address.string(),
+            data,
+        }
+    }
+}
+
+// address_to_token converts an IoTeX address into an ABI address argument
+pub fn address_to_token(addr: &address::v1::AddrV1) -> Token {
+    Token::Address(ethabi::Address::from_slice(addr.bytes()))
+}
+
+// token_to_address converts an ABI address value into an IoTeX address
+pub fn token_to_address(token: &Token) -> Result<address::v1::AddrV1, ContractError> {
+    match token {
+        Token::Address(a) => match address::from_bytes(a.as_bytes()) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(ContractError::AddressError(e)),
+        },
+        _ => Err(ContractError::AbiError(
+            ethabi::ErrorKind::InvalidData.into(),
+        )),
+    }
+}
+
+// send_execution signs execution with the signer's next nonce and sends it
+fn send_execution<C: Client, S: Signer + ?Sized>(
+    client: &C,
+    signer: &S,
+    execution: Execution,
+    gas_limit: u64,
+    gas_price: Uint,
+    chain_id: u32,
+) -> Result<Hash256b, ContractError> {
+    let nonce = client.pending_nonce(&signer.address())?;
+    let sealed = Envelope {
+        version: VERSION,
+        nonce,
+        gas_limit,
+        gas_price,
+        chain_id,
+        execution,
+    }
+    .sign(signer)?;
+    Ok(client.send_action(&sealed)?)
+}
+
+#[derive(Debug)]
+pub enum ContractError {
+    AbiError(ethabi::Error),
+    AddressError(address::AddrError),
+    AccountError(AccountError),
+    RpcError(rpc::RpcError),
+    CryptoError(crypto::Error),
+    SignerError(SignerError),
+    ExecutionFailed(u64),
+    Reverted(u64, Revert),
+    InterfaceNotSupported([u8; 4]),
+    CallFailed(Revert),
+}
+
+impl From<AccountError> for ContractError {
+    fn from(e: AccountError) -> Self {
+        ContractError::AccountError(e)
+    }
+}
+
+impl From<rpc::RpcError> for ContractError {
+    fn from(e: rpc::RpcError) -> Self {
+        ContractError::RpcError(e)
+    }
+}
+
+impl From<crypto::Error> for ContractError {
+    fn from(e: crypto::Error) -> Self {
+        ContractError::CryptoError(e)
+    }
+}
+
+impl From<SignerError> for ContractError {
+    fn from(e: SignerError) -> Self {
+        ContractError::SignerError(e)
+    }
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContractError::AbiError(e) => write!(f, "abi error, {}", e),
+            ContractError::AddressError(e) => write!(f, "address error, {}", e),
+            ContractError::AccountError(e) => write!(f, "account error, {}", e),
+            ContractError::RpcError(e) => write!(f, "rpc error, {}", e),
+            ContractError::CryptoError(e) => write!(f, "crypto error, {}", e),
+            ContractError::SignerError(e) => write!(f, "signer error, {}", e),
+            ContractError::ExecutionFailed(s) => write!(f, "execution failed with status {}", s),
+            ContractError::Reverted(s, r) => {
+                write!(f, "execution reverted with status {}, {}", s, r)
+            }
+            ContractError::InterfaceNotSupported(id) => {
+                write!(f, "interface 0x{} not supported", hex::encode(id))
+            }
+            ContractError::CallFailed(r) => write!(f, "batched call failed, {}", r),
+        }
+    }
+}
+
+impl error::Error for ContractError {
+    fn description(&self) -> &str {
+        match *self {
+            ContractError::AbiError(_) => "abi error",
+            ContractError::AddressError(_) => "address error",
+            ContractError::AccountError(_) => "account error",
+            ContractError::RpcError(_) => "rpc error",
+            ContractError::CryptoError(_) => "crypto error",
+            ContractError::SignerError(_) => "signer error",
+            ContractError::ExecutionFailed(_) => "execution failed",
+            ContractError::Reverted(_, _) => "execution reverted",
+            ContractError::InterfaceNotSupported(_) => "interface not supported",
+            ContractError::CallFailed(_) => "batched call failed",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rpc::mock::{self, MockClient};
+    use std::time::Duration;
+
+    const ABI: &str = r#"[
+        {"type":"constructor",
+         "inputs":[{"name":"supply","type":"uint256"}]},
+        {"type":"function","name":"balanceOf","constant":true,
+         "inputs":[{"name":"owner","type":"address"}],
+         "outputs":[{"name":"","type":"uint256"}]},
+        {"type":"function","name":"transfer","constant":false,
+         "inputs":[{"name":"to","type":"address"},{"name":"value","type":"uint256"}],
+         "outputs":[{"name":"","type":"bool"}]}
+    ]"#;
+    const CONTRACT: &str = "io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j";
+
+    fn contract(client: &MockClient) -> Contract<&MockClient> {
+        let addr = address::from_string(CONTRACT).unwrap();
+        Contract::new(
+            addr,
+            ABI,
+            client,
+            crate::account::account::new_account().unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_call() {
+        let client = MockClient::new();
+        let c = contract(&client);
+        let owner = address_to_token(&c.address());
+        assert_eq!(token_to_address(&owner).unwrap(), c.address());
+        assert!(token_to_address(&Token::Bool(true)).is_err());
+        client.respond(ethabi::encode(&[Token::Uint(Uint::from(42))]));
+
+        let out = c.call("balanceOf", std::slice::from_ref(&owner)).unwrap();
+        assert_eq!(out, vec![Token::Uint(Uint::from(42))]);
+        let read = client.reads.borrow()[0].clone();
+        assert_eq!(read.contract, CONTRACT);
+        assert_eq!(hex::encode(&read.data[..4]), "70a08231");
+
+        assert!(c.call("allowance", &[]).is_err());
+        assert!(c.call("balanceOf", &[]).is_err());
+
+        let mut failed = mock::success_receipt();
+        failed.status = 106;
+        client.respond_receipt(Vec::new(), failed);
+        match c.call("balanceOf", &[owner]) {
+            Err(ContractError::ExecutionFailed(106)) => (),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_execute() {
+        let client = MockClient::new();
+        client.nonce.set(7);
+        let mut c = contract(&client);
+        c.set_gas_limit(50_000);
+        let to = Token::Address(ethabi::Address::from_slice(c.address().bytes()));
+
+        let pending = c
+            .execute("transfer", &[to, Token::Uint(Uint::from(1))], Uint::from(3))
+            .unwrap();
+        assert_eq!(pending.poll().unwrap(), None);
+
+        let sent = client.sent.borrow()[0].clone();
+        assert_eq!(pending.hash(), sent.hash());
+        assert_eq!(sent.envelope().nonce, 7);
+        assert_eq!(sent.envelope().gas_limit, 50_000);
+        assert_eq!(sent.envelope().execution.amount, Uint::from(3));
+        assert_eq!(
+            hex::encode(&sent.envelope().execution.data[..4]),
+            "a9059cbb"
+        );
+        assert_eq!(hex::encode(sent.sender_pub_key()), c.signer().public_key());
+
+        client.mine(mock::success_receipt());
+        let receipt = pending.wait(3, Duration::from_millis(1)).unwrap();
+        assert_eq!(receipt.act_hash, sent.hash());
+    }
+
+    #[test]
+    fn test_deploy() {
+        let client = MockClient::new();
+        let mut receipt = mock::success_receipt();
+        receipt.contract_address = String::from(CONTRACT);
+        client.mine(receipt);
+        let acc = crate::account::account::new_account().unwrap();
+        let options = DeployOptions {
+            gas_limit: 2_000_000,
+            receipt_attempts: 1,
+            ..Default::default()
+        };
+        let bytecode = [0x60u8, 0x80, 0x60, 0x40];
+
+        let c = Contract::deploy(
+            &client,
+            acc.clone(),
+            ABI,
+            &bytecode,
+            &[Token::Uint(Uint::from(1000))],
+            &options,
+        )
+        .unwrap();
+        assert_eq!(c.address().string(), CONTRACT);
+        assert_eq!(c.signer().address(), acc.address());
+
+        let sent = client.sent.borrow()[0].clone();
+        let execution = &sent.envelope().execution;
+        assert_eq!(execution.contract, "");
+        assert_eq!(sent.envelope().gas_limit, 2_000_000);
+        assert_eq!(&execution.data[..4], &bytecode[..]);
+        assert_eq!(
+            execution.data[4..],
+            ethabi::encode(&[Token::Uint(Uint::from(1000))])[..]
+        );
+
+        let mut failed = mock::success_receipt();
+        failed.status = 106;
+        client.mine(failed);
+        match Contract::deploy(&client, acc.clone(), ABI, &bytecode, &[], &options) {
+            Err(ContractError::AbiError(_)) => (),
+            _ => panic!("constructor arguments should be required"),
+        }
+        match Contract::deploy(
+            &client,
+            acc,
+            ABI,
+            &bytecode,
+            &[Token::Uint(Uint::from(1000))],
+            &options,
+        ) {
+            Err(ContractError::ExecutionFailed(106)) => (),
+            _ => panic!("failed deployment should be reported"),
+        }
+    }
+
+    crate::abigen!(TestToken, "src/contract/testdata/token.json");
+
+    #[test]
+    fn test_abigen() {
+        let client = MockClient::new();
+        let addr = address::from_string(CONTRACT).unwrap();
+        let token = TestToken::new(
+            addr,
+            &client,
+            crate::account::account::new_account().unwrap(),
+        )
+        .unwrap();
+        let owner = ethabi::Address::from_low_u64_be(9);
+
+        client.respond(ethabi::encode(&[Token::Uint(Uint::from(42))]));
+        assert_eq!(token.balance_of(owner).unwrap(), Uint::from(42));
+
+        client.respond(ethabi::encode(&[
+            Token::Array(vec![Token::Address(owner)]),
+            Token::FixedArray(vec![
+                Token::FixedBytes(vec![1; 32]),
+                Token::FixedBytes(vec![2; 32]),
+            ]),
+        ]));
+        let (holders, tags) = token.holders().unwrap();
+        assert_eq!(holders, vec![owner]);
+        assert_eq!(tags, [[1u8; 32], [2u8; 32]]);
+
+        client.respond(Vec::new());
+        assert!(token.balance_of(owner).is_err());
+
+        let pending = token.transfer(owner, Uint::from(5)).unwrap();
+        token.mint(Uint::from(7)).unwrap();
+        // parameters whose names collide, e.g. _amount and the value sent
+        token.deposit(Uint::from(3), Uint::from(4)).unwrap();
+        token
+            .move_(owner, owner, Uint::from(1), Uint::from(2))
+            .unwrap();
+        let sent = client.sent.borrow().clone();
+        assert_eq!(pending.hash(), sent[0].hash());
+        assert_eq!(
+            hex::encode(&sent[0].envelope().execution.data[..4]),
+            "a9059cbb"
+        );
+        assert_eq!(sent[1].envelope().execution.amount, Uint::from(7));
+        assert_eq!(sent[2].envelope().execution.amount, Uint::from(4));
+        assert_eq!(
+            sent[2].envelope().execution.data[4..],
+            ethabi::encode(&[Token::Uint(Uint::from(3))])[..]
+        );
+
+        let mut receipt = mock::success_receipt();
+        receipt.logs.push(crate::action::Log {
+            contract_address: String::from(CONTRACT),
+            topics: vec![
+                crate::crypto::hash::hash256b(b"Transfer(address,address,uint256)"),
+                crate::crypto::hash::bytes_to_hash256(&[0]),
+                crate::crypto::hash::bytes_to_hash256(owner.as_bytes()),
+            ],
+            data: ethabi::encode(&[Token::Uint(Uint::from(5))]),
+            blk_height: 1,
+            act_hash: receipt.act_hash,
+            index: 0,
+        });
+        assert_eq!(
+            token.transfer_events(&receipt).unwrap(),
+            vec![TestTokenTransfer {
+                from: ethabi::Address::zero(),
+                to: owner,
+                value: Uint::from(5),
+            }]
+        );
+        assert!(token.memo_events(&receipt).unwrap().is_empty());
+        assert!(token.moved_events(&receipt).unwrap().is_empty());
+    }
 }
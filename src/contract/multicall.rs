@@ -0,0 +1,328 @@
+use super::*;
+use crate::address::v1::AddrV1;
+use ethabi::ParamType;
+
+// AGGREGATE3_SELECTOR is the selector of Multicall3's aggregate3((address,bool,bytes)[])
+pub const AGGREGATE3_SELECTOR: [u8; 4] = [0x82, 0xad, 0x56, 0xcb];
+// DEFAULT_MULTICALL_GAS_LIMIT is the gas limit of a batch read unless set otherwise
+pub const DEFAULT_MULTICALL_GAS_LIMIT: u64 = 50_000_000;
+// DEFAULT_MAX_CALLS is how many calls a batch holds unless set otherwise
+pub const DEFAULT_MAX_CALLS: usize = 200;
+// DEFAULT_MAX_DATA_SIZE is how many bytes of call data a batch holds unless set otherwise
+pub const DEFAULT_MAX_DATA_SIZE: usize = 64 * 1024;
+
+// Call is a contract call prepared for a Multicall batch
+#[derive(Clone, Debug, PartialEq)]
+pub struct Call {
+    target: AddrV1,
+    function: ethabi::Function,
+    errors: Vec<CustomError>,
+    data: Vec<u8>,
+    allow_failure: bool,
+}
+
+impl Call {
+    // new prepares a call of method with params on contract; the call may fail
+    // without failing the rest of its batch
+    pub fn new<C: Client>(
+        contract: &Contract<C>,
+        method: &str,
+        params: &[Token],
+    ) -> Result<Call, ContractError> {
+        let data = contract.encode(method, params)?;
+        Ok(Call {
+            target: contract.address(),
+            function: contract.function(method)?.clone(),
+            errors: contract.errors().to_vec(),
+            data: data.raw,
+            allow_failure: true,
+        })
+    }
+    // target returns the address of the called contract
+    pub fn target(&self) -> AddrV1 {
+        self.target
+    }
+    // data returns the encoded call
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    // set_allow_failure sets whether the call may fail; a failing call that is
+    // not allowed to fail reverts its whole batch
+    pub fn set_allow_failure(&mut self, allow_failure: bool) {
+        self.allow_failure = allow_failure;
+    }
+
+    fn decode(&self, success: bool, data: &[u8]) -> Result<Vec<Token>, ContractError> {
+        if !success {
+            return Err(ContractError::CallFailed(revert::decode_revert(
+                &self.errors,
+                data,
+            )));
+        }
+        match self.function.decode_output(data) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(ContractError::AbiError(e)),
+        }
+    }
+}
+
+// Multicall batches contract calls into aggregate3 reads of a Multicall3
+// contract, splitting batches larger than the configured limits
+pub struct Multicall<C: Client> {
+    address: AddrV1,
+    client: C,
+    caller: AddrV1,
+    gas_limit: u64,
+    max_calls: usize,
+    max_data_size: usize,
+    calls: Vec<Call>,
+}
+
+impl<C: Client> Multicall<C> {
+    // new binds the Multicall3 contract deployed at address; reads are made on
+    // behalf of caller
+    pub fn new(address: AddrV1, client: C, caller: AddrV1) -> Multicall<C> {
+        Multicall {
+            address,
+            client,
+            caller,
+            gas_limit: DEFAULT_MULTICALL_GAS_LIMIT,
+            max_calls: DEFAULT_MAX_CALLS,
+            max_data_size: DEFAULT_MAX_DATA_SIZE,
+            calls: Vec::new(),
+        }
+    }
+    // set_gas_limit sets the gas limit of each batch read
+    pub fn set_gas_limit(&mut self, gas_limit: u64) {
+        self.gas_limit = gas_limit;
+    }
+    // set_max_calls sets how many calls a batch holds at most
+    pub fn set_max_calls(&mut self, max_calls: usize) {
+        self.max_calls = max_calls.max(1);
+    }
+    // set_max_data_size sets how many bytes of call data a batch holds at most;
+    // a single larger call still gets a batch of its own
+    pub fn set_max_data_size(&mut self, max_data_size: usize) {
+        self.max_data_size = max_data_size;
+    }
+    // add queues call
+    pub fn add(&mut self, call: Call) {
+        self.calls.push(call);
+    }
+    // add_call prepares and queues a call of method with params on contract
+    pub fn add_call<D: Client>(
+        &mut self,
+        contract: &Contract<D>,
+        method: &str,
+        params: &[Token],
+    ) -> Result<(), ContractError> {
+        self.add(Call::new(contract, method, params)?);
+        Ok(())
+    }
+    // calls returns the queued calls
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+    // clear drops the queued calls
+    pub fn clear(&mut self) {
+        self.calls.clear();
+    }
+    // batches splits the queued calls into batches within the limits
+    pub fn batches(&self) -> Vec<&[Call]> {
+        let mut batches = Vec::new();
+        let (mut start, mut size) = (0, 0);
+        for (i, call) in self.calls.iter().enumerate() {
+            if i > start
+                && (i - start == self.max_calls || size + call.data.len() > self.max_data_size)
+            {
+                batches.push(&self.calls[start..i]);
+                start = i;
+                size = 0;
+            }
+            size += call.data.len();
+        }
+        if start < self.calls.len() {
+            batches.push(&self.calls[start..]);
+        }
+        batches
+    }
+    // call reads every queued call, one aggregate3 read per batch, and returns
+    // the decoded outputs or failure of each call in order. An error is returned
+    // only if a whole batch fails, e.g. because a call not allowed to fail reverted.
+    pub fn call(&self) -> Result<Vec<Result<Vec<Token>, ContractError>>, ContractError> {
+        let mut results = Vec::with_capacity(self.calls.len());
+        for batch in self.batches() {
+            let execution = Execution {
+                amount: Uint::zero(),
+                contract: self.address.string(),
+                data: encode_aggregate3(batch),
+            };
+            let res = self
+                .client
+                .read_contract(&execution, &self.caller, self.gas_limit)?;
+            revert::check_receipt(&[], &res.receipt, &res.data)?;
+            let outputs = decode_aggregate3(&res.data)?;
+            if outputs.len() != batch.len() {
+                return Err(invalid_data());
+            }
+            for (call, (success, data)) in batch.iter().zip(outputs) {
+                results.push(call.decode(success, &data));
+            }
+        }
+        Ok(results)
+    }
+}
+
+// encode_aggregate3 encodes an aggregate3 call of calls
+fn encode_aggregate3(calls: &[Call]) -> Vec<u8> {
+    let tuples = calls
+        .iter()
+        .map(|c| {
+            ethabi::encode(&[
+                address_to_token(&c.target),
+                Token::Bool(c.allow_failure),
+                Token::Bytes(c.data.clone()),
+            ])
+        })
+        .collect();
+    let mut data = AGGREGATE3_SELECTOR.to_vec();
+    data.extend(encode_tuple_array(tuples));
+    data
+}
+
+// encode_tuple_array encodes an array of encoded dynamic tuples as the single
+// parameter of a call or output; ethabi has no tuple support
+fn encode_tuple_array(tuples: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut data = word(32);
+    data.extend(word(tuples.len()));
+    let mut offset = 32 * tuples.len();
+    for tuple in tuples.iter() {
+        data.extend(word(offset));
+        offset += tuple.len();
+    }
+    for tuple in tuples {
+        data.extend(tuple);
+    }
+    data
+}
+
+// decode_aggregate3 decodes the (bool success, bytes returnData)[] output of aggregate3
+fn decode_aggregate3(data: &[u8]) -> Result<Vec<(bool, Vec<u8>)>, ContractError> {
+    let array = &data[read_offset(data, 0)?..];
+    let len = read_offset(array, 0)?;
+    let items = &array[32..];
+    if len > items.len() / 32 {
+        return Err(invalid_data());
+    }
+    let mut results = Vec::with_capacity(len);
+    for i in 0..len {
+        let tuple = &items[read_offset(items, 32 * i)?..];
+        let mut decoded = match ethabi::decode(&[ParamType::Bool, ParamType::Bytes], tuple) {
+            Ok(r) => r.into_iter(),
+            Err(e) => return Err(ContractError::AbiError(e)),
+        };
+        match (decoded.next(), decoded.next()) {
+            (Some(Token::Bool(success)), Some(Token::Bytes(data))) => results.push((success, data)),
+            _ => return Err(invalid_data()),
+        }
+    }
+    Ok(results)
+}
+
+// read_offset reads the word of data at index as an offset or length within data
+fn read_offset(data: &[u8], index: usize) -> Result<usize, ContractError> {
+    if data.len() < index + 32 {
+        return Err(invalid_data());
+    }
+    let value = Uint::from(&data[index..index + 32]);
+    if value > Uint::from(data.len()) {
+        return Err(invalid_data());
+    }
+    Ok(value.low_u64() as usize)
+}
+
+fn word(value: usize) -> Vec<u8> {
+    ethabi::encode(&[Token::Uint(Uint::from(value))])
+}
+
+fn invalid_data() -> ContractError {
+    ContractError::AbiError(ethabi::ErrorKind::InvalidData.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::crypto::hash;
+    use crate::rpc::mock::MockClient;
+
+    const ABI: &str = r#"[
+        {"type":"function","name":"balanceOf","constant":true,
+         "inputs":[{"name":"owner","type":"address"}],
+         "outputs":[{"name":"","type":"uint256"}]},
+        {"type":"error","name":"Paused","inputs":[]}
+    ]"#;
+    const TOKEN: &str = "io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j";
+
+    fn results(results: &[(bool, Vec<u8>)]) -> Vec<u8> {
+        encode_tuple_array(
+            results
+                .iter()
+                .map(|(success, data)| {
+                    ethabi::encode(&[Token::Bool(*success), Token::Bytes(data.clone())])
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_multicall() {
+        let selector = hash::hash256b(b"aggregate3((address,bool,bytes)[])");
+        assert_eq!(selector.0[..4], AGGREGATE3_SELECTOR);
+
+        let client = MockClient::new();
+        let acc = crate::account::account::new_account().unwrap();
+        let token = address::from_string(TOKEN).unwrap();
+        let c = Contract::new(token, ABI, &client, acc.clone()).unwrap();
+        let multicall = address::from_bytes(&[0xca; 20]).unwrap();
+        let mut m = Multicall::new(multicall, &client, acc.address());
+        m.set_max_calls(2);
+        for _ in 0..3 {
+            m.add_call(&c, "balanceOf", &[address_to_token(&acc.address())])
+                .unwrap();
+        }
+        assert_eq!(m.batches().len(), 2);
+        assert!(m.add_call(&c, "balanceOf", &[]).is_err());
+
+        let paused = c.errors()[0].selector().to_vec();
+        client.respond(results(&[
+            (true, ethabi::encode(&[Token::Uint(Uint::from(1))])),
+            (false, paused),
+        ]));
+        client.respond(results(&[(
+            true,
+            ethabi::encode(&[Token::Uint(Uint::from(3))]),
+        )]));
+        let out = m.call().unwrap();
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[0].as_ref().unwrap(), &vec![Token::Uint(Uint::from(1))]);
+        match &out[1] {
+            Err(ContractError::CallFailed(Revert::Custom { name, .. })) => {
+                assert_eq!(name, "Paused")
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert_eq!(out[2].as_ref().unwrap(), &vec![Token::Uint(Uint::from(3))]);
+
+        let reads = client.reads.borrow().clone();
+        assert_eq!(reads[0].contract, multicall.string());
+        assert_eq!(reads[0].data[..4], AGGREGATE3_SELECTOR);
+        assert_eq!(read_offset(&reads[0].data[4..], 32).unwrap(), 2);
+        assert_eq!(read_offset(&reads[1].data[4..], 32).unwrap(), 1);
+
+        m.set_max_calls(10);
+        m.set_max_data_size(40);
+        assert_eq!(m.batches().len(), 3);
+        m.clear();
+        assert!(m.call().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+use super::*;
+use crate::crypto::hash;
+use ethabi::ParamType;
+
+// encode_packed encodes tokens of the given types like Solidity's
+// abi.encodePacked: values take the size of their type without padding, and
+// array elements are padded to 32 bytes. Types are required since a Token does
+// not carry the bit size of integers.
+pub fn encode_packed(types: &[ParamType], tokens: &[Token]) -> Result<Vec<u8>, ContractError> {
+    if types.len() != tokens.len() {
+        return Err(invalid_data());
+    }
+    let mut packed = Vec::new();
+    for (kind, token) in types.iter().zip(tokens) {
+        pack(kind, token, false, &mut packed)?;
+    }
+    Ok(packed)
+}
+
+// hash_packed returns keccak256(abi.encodePacked(tokens)), the hash contracts
+// usually recover signers from
+pub fn hash_packed(types: &[ParamType], tokens: &[Token]) -> Result<Hash256b, ContractError> {
+    Ok(hash::hash256b(&encode_packed(types, tokens)?))
+}
+
+// pack appends the packed encoding of token to packed; elements of arrays are
+// padded to 32 bytes
+fn pack(
+    kind: &ParamType,
+    token: &Token,
+    in_array: bool,
+    packed: &mut Vec<u8>,
+) -> Result<(), ContractError> {
+    match (kind, token) {
+        (ParamType::Address, Token::Address(a)) => {
+            if in_array {
+                packed.extend_from_slice(&[0; 12]);
+            }
+            packed.extend_from_slice(a.as_bytes());
+        }
+        (ParamType::Bool, Token::Bool(b)) => {
+            if in_array {
+                packed.extend_from_slice(&[0; 31]);
+            }
+            packed.push(*b as u8);
+        }
+        (ParamType::Uint(size), Token::Uint(v)) => {
+            check_size(*size)?;
+            if v.bits() > *size {
+                return Err(invalid_data());
+            }
+            pack_word(*v, *size, in_array, packed);
+        }
+        (ParamType::Int(size), Token::Int(v)) => {
+            check_size(*size)?;
+            // v is the two's complement of the value over 256 bits
+            let magnitude = if v.bit(255) { !*v } else { *v };
+            if magnitude.bits() >= *size {
+                return Err(invalid_data());
+            }
+            pack_word(*v, *size, in_array, packed);
+        }
+        (ParamType::FixedBytes(size), Token::FixedBytes(b)) => {
+            if b.len() != *size || *size == 0 || *size > 32 {
+                return Err(invalid_data());
+            }
+            packed.extend_from_slice(b);
+            if in_array {
+                packed.extend(std::iter::repeat(0).take(32 - size));
+            }
+        }
+        (ParamType::Bytes, Token::Bytes(b)) if !in_array => packed.extend_from_slice(b),
+        (ParamType::String, Token::String(s)) if !in_array => {
+            packed.extend_from_slice(s.as_bytes())
+        }
+        (ParamType::Array(inner), Token::Array(values)) if !in_array => {
+            pack_array(inner, values, packed)?
+        }
+        (ParamType::FixedArray(inner, len), Token::FixedArray(values))
+            if !in_array && values.len() == *len =>
+        {
+            pack_array(inner, values, packed)?
+        }
+        _ => return Err(invalid_data()),
+    }
+    Ok(())
+}
+
+// pack_array packs the elements of an array; Solidity cannot pack arrays of
+// dynamic types, nor nested arrays
+fn pack_array(
+    inner: &ParamType,
+    values: &[Token],
+    packed: &mut Vec<u8>,
+) -> Result<(), ContractError> {
+    for value in values {
+        pack(inner, value, true, packed)?;
+    }
+    Ok(())
+}
+
+// pack_word appends the size bits of v, or all 32 bytes in arrays
+fn pack_word(v: Uint, size: usize, in_array: bool, packed: &mut Vec<u8>) {
+    let mut word = [0u8; 32];
+    v.to_big_endian(&mut word);
+    let len = if in_array { 32 } else { size / 8 };
+    packed.extend_from_slice(&word[32 - len..]);
+}
+
+fn check_size(size: usize) -> Result<(), ContractError> {
+    if size == 0 || size > 256 || size % 8 != 0 {
+        return Err(invalid_data());
+    }
+    Ok(())
+}
+
+fn invalid_data() -> ContractError {
+    ContractError::AbiError(ethabi::ErrorKind::InvalidData.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encode_packed() {
+        // example from the Solidity documentation
+        let packed = encode_packed(
+            &[
+                ParamType::Int(16),
+                ParamType::FixedBytes(1),
+                ParamType::Uint(16),
+                ParamType::String,
+            ],
+            &[
+                Token::Int(!Uint::zero()),
+                Token::FixedBytes(vec![0x42]),
+                Token::Uint(Uint::from(3)),
+                Token::String(String::from("Hello, world!")),
+            ],
+        )
+        .unwrap();
+        assert_eq!(hex::encode(packed), "ffff42000348656c6c6f2c20776f726c6421");
+
+        let addr = ethabi::Address::from_slice(&[0x11; 20]);
+        let packed = encode_packed(
+            &[
+                ParamType::Address,
+                ParamType::Bool,
+                ParamType::Array(Box::new(ParamType::Uint(8))),
+            ],
+            &[
+                Token::Address(addr),
+                Token::Bool(true),
+                Token::Array(vec![Token::Uint(Uint::from(1)), Token::Uint(Uint::from(2))]),
+            ],
+        )
+        .unwrap();
+        assert_eq!(packed.len(), 20 + 1 + 64);
+        assert_eq!(packed[20], 1);
+        assert_eq!(packed[52], 1);
+        assert_eq!(packed[84], 2);
+
+        // keccak256(abi.encodePacked(uint256(1))) is keccak256 of the 32 byte word
+        let one = [Token::Uint(Uint::from(1))];
+        assert_eq!(
+            hash_packed(&[ParamType::Uint(256)], &one).unwrap(),
+            hash::hash256b(&ethabi::encode(&one))
+        );
+
+        let invalid: [(ParamType, Token); 5] = [
+            (ParamType::Uint(8), Token::Uint(Uint::from(256))),
+            (ParamType::Int(8), Token::Int(Uint::from(128))),
+            (ParamType::FixedBytes(2), Token::FixedBytes(vec![1])),
+            (
+                ParamType::Array(Box::new(ParamType::String)),
+                Token::Array(vec![Token::String(String::new())]),
+            ),
+            (ParamType::Bool, Token::Uint(Uint::one())),
+        ];
+        for (kind, token) in invalid.iter() {
+            assert!(
+                encode_packed(std::slice::from_ref(kind), std::slice::from_ref(token)).is_err()
+            );
+        }
+        assert_eq!(
+            encode_packed(&[ParamType::Int(8)], &[Token::Int(!Uint::from(127))]).unwrap(),
+            vec![0x80]
+        );
+    }
+}
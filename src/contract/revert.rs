@@ -0,0 +1,280 @@
+use super::*;
+use crate::action::Receipt;
+use crate::crypto::hash;
+use ethabi::{Param, ParamType};
+
+// ERROR_SELECTOR is the selector of Solidity's Error(string)
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+// PANIC_SELECTOR is the selector of Solidity's Panic(uint256)
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+// CustomError is a Solidity custom error declared in the contract ABI
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomError {
+    pub name: String,
+    pub inputs: Vec<Param>,
+}
+
+impl CustomError {
+    // signature returns the canonical signature, e.g. InsufficientBalance(uint256,uint256)
+    pub fn signature(&self) -> String {
+        let types: Vec<String> = self.inputs.iter().map(|p| p.kind.to_string()).collect();
+        format!("{}({})", self.name, types.join(","))
+    }
+    // selector returns the first 4 bytes of the signature's hash
+    pub fn selector(&self) -> [u8; 4] {
+        let h = hash::hash256b(self.signature().as_bytes());
+        [h.0[0], h.0[1], h.0[2], h.0[3]]
+    }
+}
+
+// Revert is the decoded reason of a reverted execution
+#[derive(Clone, Debug, PartialEq)]
+pub enum Revert {
+    // Error is a require/revert with a reason string
+    Error(String),
+    // Panic is a failed assert, overflow, division by zero, ...
+    Panic(Uint),
+    // Custom is an ABI-defined custom error
+    Custom { name: String, params: Vec<Token> },
+    // Message is a plain revert message reported by the node
+    Message(String),
+    // Raw is revert data matching no known error
+    Raw(Vec<u8>),
+}
+
+impl Revert {
+    // panic_reason describes a Solidity panic code
+    pub fn panic_reason(code: Uint) -> Option<&'static str> {
+        if code > Uint::from(0xffu64) {
+            return None;
+        }
+        match code.low_u64() {
+            0x00 => Some("generic compiler panic"),
+            0x01 => Some("assertion failed"),
+            0x11 => Some("arithmetic overflow or underflow"),
+            0x12 => Some("division or modulo by zero"),
+            0x21 => Some("invalid enum value"),
+            0x22 => Some("invalid storage byte array encoding"),
+            0x31 => Some("pop on empty array"),
+            0x32 => Some("array index out of bounds"),
+            0x41 => Some("out of memory"),
+            0x51 => Some("call to zero-initialized function"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Revert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Revert::Error(msg) => write!(f, "{}", msg),
+            Revert::Panic(code) => match Revert::panic_reason(*code) {
+                Some(reason) => write!(f, "panic 0x{:x}: {}", code, reason),
+                None => write!(f, "panic 0x{:x}", code),
+            },
+            Revert::Custom { name, params } => {
+                let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+                write!(f, "{}({})", name, params.join(", "))
+            }
+            Revert::Message(msg) => write!(f, "{}", msg),
+            Revert::Raw(data) => write!(f, "0x{}", hex::encode(data)),
+        }
+    }
+}
+
+impl<C: Client> Contract<C> {
+    // errors returns the custom errors declared in the ABI
+    pub fn errors(&self) -> &[CustomError] {
+        &self.errors
+    }
+    // decode_revert decodes revert data against the standard and custom errors
+    pub fn decode_revert(&self, data: &[u8]) -> Revert {
+        decode_revert(&self.errors, data)
+    }
+    // check_receipt turns a failed receipt into a ContractError, decoding its revert reason
+    pub fn check_receipt(&self, receipt: &Receipt) -> Result<(), ContractError> {
+        check_receipt(&self.errors, receipt, &[])
+    }
+}
+
+// decode_revert decodes revert data against Error(string), Panic(uint256) and errors
+pub fn decode_revert(errors: &[CustomError], data: &[u8]) -> Revert {
+    if data.len() < 4 {
+        return Revert::Raw(data.to_vec());
+    }
+    let (selector, args) = data.split_at(4);
+    let decoded = if selector == ERROR_SELECTOR {
+        match ethabi::decode(&[ParamType::String], args) {
+            Ok(mut r) => match r.pop() {
+                Some(Token::String(msg)) => Some(Revert::Error(msg)),
+                _ => None,
+            },
+            Err(_) => None,
+        }
+    } else if selector == PANIC_SELECTOR {
+        match ethabi::decode(&[ParamType::Uint(256)], args) {
+            Ok(mut r) => match r.pop() {
+                Some(Token::Uint(code)) => Some(Revert::Panic(code)),
+                _ => None,
+            },
+            Err(_) => None,
+        }
+    } else {
+        errors
+            .iter()
+            .find(|e| e.selector() == selector)
+            .and_then(|e| {
+                let types: Vec<ParamType> = e.inputs.iter().map(|p| p.kind.clone()).collect();
+                match ethabi::decode(&types, args) {
+                    Ok(params) => Some(Revert::Custom {
+                        name: e.name.clone(),
+                        params,
+                    }),
+                    Err(_) => None,
+                }
+            })
+    };
+    decoded.unwrap_or_else(|| Revert::Raw(data.to_vec()))
+}
+
+// check_receipt returns the decoded revert of a failed receipt; data is the
+// return value of the execution, if known
+pub(super) fn check_receipt(
+    errors: &[CustomError],
+    receipt: &Receipt,
+    data: &[u8],
+) -> Result<(), ContractError> {
+    if receipt.succeeded() {
+        return Ok(());
+    }
+    let msg = receipt.execution_revert_msg.trim();
+    let revert = if !data.is_empty() {
+        decode_revert(errors, data)
+    } else if msg.starts_with("0x") && msg.len() > 2 {
+        match hex::decode(&msg[2..]) {
+            Ok(raw) => decode_revert(errors, &raw),
+            Err(_) => Revert::Message(String::from(msg)),
+        }
+    } else if !msg.is_empty() {
+        Revert::Message(String::from(msg))
+    } else {
+        return Err(ContractError::ExecutionFailed(receipt.status));
+    };
+    Err(ContractError::Reverted(receipt.status, revert))
+}
+
+// load_abi loads abi_json, setting aside the custom errors ethabi cannot parse
+pub(super) fn load_abi(
+    abi_json: &str,
+) -> Result<(ethabi::Contract, Vec<CustomError>), ContractError> {
+    let entries: Vec<serde_json::Value> = match serde_json::from_str(abi_json) {
+        Ok(r) => r,
+        Err(e) => return Err(ContractError::AbiError(e.into())),
+    };
+    let mut errors = Vec::new();
+    let mut rest = Vec::new();
+    for entry in entries {
+        match entry.get("type").and_then(|t| t.as_str()) {
+            Some("error") => {
+                let name = match entry.get("name").and_then(|n| n.as_str()) {
+                    Some(r) => String::from(r),
+                    None => {
+                        return Err(ContractError::AbiError(
+                            ethabi::ErrorKind::InvalidData.into(),
+                        ))
+                    }
+                };
+                let inputs = match entry.get("inputs") {
+                    Some(v) => match serde_json::from_value(v.clone()) {
+                        Ok(r) => r,
+                        Err(e) => return Err(ContractError::AbiError(e.into())),
+                    },
+                    None => Vec::new(),
+                };
+                errors.push(CustomError { name, inputs });
+            }
+            Some("receive") => (),
+            _ => rest.push(entry),
+        }
+    }
+    match serde_json::from_value(serde_json::Value::Array(rest)) {
+        Ok(abi) => Ok((abi, errors)),
+        Err(e) => Err(ContractError::AbiError(e.into())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rpc::mock::{self, MockClient};
+
+    const ABI: &str = r#"[
+        {"type":"function","name":"withdraw","constant":false,
+         "inputs":[{"name":"amount","type":"uint256"}],"outputs":[]},
+        {"type":"error","name":"InsufficientBalance",
+         "inputs":[{"name":"available","type":"uint256"},{"name":"required","type":"uint256"}]},
+        {"type":"receive","stateMutability":"payable"}
+    ]"#;
+
+    #[test]
+    fn test_decode_revert() {
+        let client = MockClient::new();
+        let addr = address::from_string("io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j").unwrap();
+        let acc = crate::account::account::new_account().unwrap();
+        let c = Contract::new(addr, ABI, &client, acc).unwrap();
+        assert_eq!(
+            c.errors()[0].signature(),
+            "InsufficientBalance(uint256,uint256)"
+        );
+        assert_eq!(hex::encode(c.errors()[0].selector()), "cf479181");
+
+        let mut data = ERROR_SELECTOR.to_vec();
+        data.extend(ethabi::encode(&[Token::String(String::from("not owner"))]));
+        assert_eq!(
+            c.decode_revert(&data),
+            Revert::Error(String::from("not owner"))
+        );
+
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend(ethabi::encode(&[Token::Uint(Uint::from(0x11))]));
+        let revert = c.decode_revert(&data);
+        assert_eq!(revert, Revert::Panic(Uint::from(0x11)));
+        assert_eq!(
+            revert.to_string(),
+            "panic 0x11: arithmetic overflow or underflow"
+        );
+
+        let mut data = c.errors()[0].selector().to_vec();
+        let params = vec![Token::Uint(Uint::from(1)), Token::Uint(Uint::from(2))];
+        data.extend(ethabi::encode(&params));
+        assert_eq!(
+            c.decode_revert(&data),
+            Revert::Custom {
+                name: String::from("InsufficientBalance"),
+                params: params.clone(),
+            }
+        );
+
+        let mut failed = mock::success_receipt();
+        failed.status = 106;
+        client.respond_receipt(data, failed.clone());
+        match c.call("withdraw", &[Token::Uint(Uint::from(2))]) {
+            Err(ContractError::Reverted(106, Revert::Custom { name, .. })) => {
+                assert_eq!(name, "InsufficientBalance")
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        failed.execution_revert_msg = String::from("not owner");
+        match c.check_receipt(&failed) {
+            Err(ContractError::Reverted(106, Revert::Message(msg))) => assert_eq!(msg, "not owner"),
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert_eq!(
+            c.decode_revert(&[0xde, 0xad, 0xbe, 0xef]),
+            Revert::Raw(vec![0xde, 0xad, 0xbe, 0xef])
+        );
+        assert!(c.check_receipt(&mock::success_receipt()).is_ok());
+    }
+}
@@ -1,14 +1,57 @@
 use super::constants::*;
+use std::convert::TryFrom;
+use std::{array, fmt, str};
 use tiny_keccak::Keccak;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Hash160b(pub [u8; HASH_160_SIZE]);
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Hash256b(pub [u8; HASH_256_SIZE]);
 
+// impl_hash implements hex Display and FromStr, the latter accepting a 0x
+// prefix, and conversions from and to byte slices
+macro_rules! impl_hash {
+    ($name:ident) => {
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", hex::encode(self.0))
+            }
+        }
+
+        impl str::FromStr for $name {
+            type Err = hex::FromHexError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let s = s
+                    .strip_prefix("0x")
+                    .or_else(|| s.strip_prefix("0X"))
+                    .unwrap_or(s);
+                let mut h = $name(Default::default());
+                hex::decode_to_slice(s, &mut h.0)?;
+                Ok(h)
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = array::TryFromSliceError;
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                Ok($name(TryFrom::try_from(bytes)?))
+            }
+        }
+    };
+}
+
+impl_hash!(Hash160b);
+impl_hash!(Hash256b);
+
 pub fn hash160b(x: &[u8]) -> Hash160b {
     let mut h256: [u8; HASH_256_SIZE] = [0; HASH_256_SIZE];
-    Keccak::keccak256(&x[..], &mut h256);
+    Keccak::keccak256(x, &mut h256);
 
     let mut res: [u8; HASH_160_SIZE] = [0; HASH_160_SIZE];
     for i in 0..HASH_160_SIZE {
@@ -19,7 +62,7 @@ pub fn hash160b(x: &[u8]) -> Hash160b {
 
 pub fn hash256b(x: &[u8]) -> Hash256b {
     let mut res: [u8; HASH_256_SIZE] = [0; HASH_256_SIZE];
-    Keccak::keccak256(&x[..], &mut res);
+    Keccak::keccak256(x, &mut res);
     Hash256b(res)
 }
 
@@ -47,6 +90,19 @@ pub fn bytes_to_hash256(bytes: &[u8]) -> Hash256b {
     Hash256b(res)
 }
 
+// personal_message prefixes message as EIP-191 personal_sign does:
+// "\x19Ethereum Signed Message:\n" + len(message) + message
+pub fn personal_message(message: &[u8]) -> Vec<u8> {
+    let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    prefixed.extend_from_slice(message);
+    prefixed
+}
+
+// personal_message_hash returns the hash signed by personal_sign
+pub fn personal_message_hash(message: &[u8]) -> Hash256b {
+    hash256b(&personal_message(message))
+}
+
 #[test]
 fn test_hash() {
     let tests: [(&str, &str); 2] = [
@@ -66,4 +122,20 @@ fn test_hash() {
         let h = hash160b(test.0.as_bytes());
         assert_eq!(hex::encode(h.0), test.1[24..]);
     }
+
+    let h = hash256b(b"abc");
+    assert_eq!(h.to_string(), tests[1].1);
+    assert_eq!(format!("0x{}", h).parse::<Hash256b>(), Ok(h));
+    assert_eq!(Hash256b::try_from(h.as_ref()).unwrap(), h);
+    assert!(Hash160b::try_from(h.as_ref()).is_err());
+    assert!(tests[1].1.parse::<Hash160b>().is_err());
+    assert!(Hash256b([0; 32]) < h);
+}
+
+#[test]
+fn test_personal_message_hash() {
+    assert_eq!(
+        hex::encode(personal_message_hash(b"Some data").0),
+        "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655"
+    );
 }
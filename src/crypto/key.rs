@@ -1,10 +1,11 @@
 use super::hash;
 use hex;
 use rand::rngs::OsRng;
-use secp256k1::{Message, PublicKey, SecretKey, Signature};
 use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, PublicKey, SecretKey, Signature};
+use std::{fmt, ptr, sync::atomic};
 
-use super::{Error, SECP256K1, SECP256K1_VERIFY};
+use super::{constants::HASH_256_SIZE, Error, SECP256K1, SECP256K1_VERIFY};
 
 pub trait PrivateKey {
     fn hex_string(&self) -> String;
@@ -12,26 +13,71 @@ pub trait PrivateKey {
     fn sign(&self, data: &[u8]) -> [u8; 65];
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+// PrivKey is a secp256k1 private key. It is deliberately not Copy: its bytes
+// are wiped when it is dropped, and they only leave it through the export_*
+// methods.
+#[derive(Clone, PartialEq)]
 pub struct PrivKey {
     bytes: [u8; 32],
 }
 
+impl fmt::Debug for PrivKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PrivKey(<redacted>)")
+    }
+}
+
+impl Drop for PrivKey {
+    fn drop(&mut self) {
+        wipe(&mut self.bytes);
+    }
+}
+
+// wipe zeroes secret bytes before their memory is released
+pub(crate) fn wipe(bytes: &mut [u8]) {
+    for b in bytes.iter_mut() {
+        // volatile so that the wipe of a dead value is not optimized out
+        unsafe { ptr::write_volatile(b, 0) };
+    }
+    atomic::compiler_fence(atomic::Ordering::SeqCst);
+}
+
 impl PrivKey {
-    pub fn from_slice(raw: &[u8; 32]) -> Self {
-        PrivKey { bytes: *raw }
+    // from_slice builds a key from raw bytes, which must be a non-zero scalar
+    // below the secp256k1 curve order
+    pub fn from_slice(raw: &[u8; 32]) -> Result<Self, Error> {
+        match SecretKey::from_slice(raw) {
+            Ok(_) => Ok(PrivKey { bytes: *raw }),
+            Err(_) => Err(Error::InvalidPrivateKey),
+        }
     }
 
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         let mut rng = OsRng::new().expect("OsRng");
         let (secret_key, _public_key) = &SECP256K1.generate_keypair(&mut rng);
+        // copy the bytes directly; going through hex would leave the secret in
+        // a String that is never wiped
         let mut bytes = [0u8; 32];
-        hex::decode_to_slice(secret_key.to_string(), &mut bytes as &mut [u8]).unwrap();
+        bytes.copy_from_slice(&secret_key[..]);
         PrivKey { bytes }
     }
 
+    #[deprecated(note = "use export_hex, which makes the secret export explicit")]
     pub fn hex_string(&self) -> String {
-        hex::encode(&self.bytes)
+        self.export_hex()
+    }
+
+    // export_bytes returns a copy of the raw secret bytes; callers are
+    // responsible for not leaking them
+    pub fn export_bytes(&self) -> [u8; 32] {
+        self.bytes
+    }
+
+    // export_hex returns the secret bytes hex encoded; callers are responsible
+    // for not leaking them
+    pub fn export_hex(&self) -> String {
+        hex::encode(self.bytes)
     }
 
     pub fn public_key(&self) -> String {
@@ -41,11 +87,14 @@ impl PrivKey {
     }
 
     pub fn sign(&self, data: &[u8]) -> [u8; 65] {
-        let hash = hash::hash256b(data);
+        self.sign_digest(&hash::hash256b(data))
+    }
 
+    // sign_digest signs a pre-computed 32 byte digest, without hashing it again
+    pub fn sign_digest(&self, digest: &hash::Hash256b) -> [u8; 65] {
         let secret_key = SecretKey::from_slice(&self.bytes).expect("32 bytes, within curve order");
 
-        let message = Message::from_slice(&hash.0).expect("32 bytes");
+        let message = Message::from_slice(&digest.0).expect("32 bytes");
 
         let sig = &SECP256K1.sign_recoverable(&message, &secret_key);
 
@@ -58,8 +107,19 @@ impl PrivKey {
 }
 
 pub fn verify_sig(data: &[u8], sig: &[u8], public_key_string: String) -> Result<bool, Error> {
-    let hash = hash::hash256b(data);
-    let message = Message::from_slice(&hash.0).expect("32 bytes");
+    verify_digest(&hash::hash256b(data), sig, public_key_string)
+}
+
+// verify_digest verifies a signature of a pre-computed 32 byte digest
+pub fn verify_digest(
+    digest: &hash::Hash256b,
+    sig: &[u8],
+    public_key_string: String,
+) -> Result<bool, Error> {
+    if sig.len() < 64 {
+        return Err(Error::InvalidSignature);
+    }
+    let message = Message::from_slice(&digest.0).expect("32 bytes");
     let signature = match Signature::from_compact(&sig[..64]) {
         Ok(r) => r,
         Err(_) => return Err(Error::InvalidSignature),
@@ -78,27 +138,52 @@ pub fn verify_sig(data: &[u8], sig: &[u8], public_key_string: String) -> Result<
     }
 }
 
+// public_key_hash returns the hash160 of a hex encoded public key, which must
+// be a valid secp256k1 point, compressed or not
 pub fn public_key_hash(pubkey: String) -> Result<hash::Hash160b, Error> {
-    let bytes = match hex::decode(pubkey) {
-        Ok(r) => r,
-        Err(_) => return Err(Error::InvalidPublicKey),
+    let key = match hex::decode(pubkey).map(|b| PublicKey::from_slice(&b)) {
+        Ok(Ok(r)) => r,
+        _ => return Err(Error::InvalidPublicKey),
     };
-    Ok(hash::hash160b(&bytes[1..]))
+    Ok(hash::hash160b(&key.serialize_uncompressed()[1..]))
 }
 
+// hex_string_to_private parses a hex encoded private key, with or without a 0x prefix
 pub fn hex_string_to_private(hex_string: String) -> Result<PrivKey, Error> {
-    let mut data: [u8; 32] = [0; 32];
-    match hex::decode_to_slice(hex_string, &mut data) {
-        Ok(_) => Ok(PrivKey { bytes: data }),
+    let trimmed = hex_string.trim();
+    let digits = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    let mut data = PrivKey { bytes: [0; 32] };
+    match hex::decode_to_slice(digits, &mut data.bytes) {
+        Ok(_) => PrivKey::from_slice(&data.bytes),
         Err(_) => Err(Error::InvalidPrivateKey),
     }
 }
 
+// recover returns the uncompressed public key that signed the 32 byte message
 pub fn recover(message: &[u8], signature: &[u8]) -> Result<[u8; 65], Error> {
-    let msg = Message::from_slice(&message).unwrap();
-    let rec_id = RecoveryId::from_i32(signature[64] as i32).map_err(|_err| Error::InvalidSignature)?;
-    let sig = RecoverableSignature::from_compact(&signature[0..64], rec_id).map_err(|_err| Error::InvalidSignature)?;
-    let rec_pubkey = &SECP256K1.recover(&msg, &sig).map_err(|_err|Error::InvalidSignature)?;
+    if message.len() != HASH_256_SIZE {
+        return Err(Error::InvalidMessageLen(message.len()));
+    }
+    recover_digest(&hash::bytes_to_hash256(message), signature)
+}
+
+// recover_digest returns the uncompressed public key that signed a
+// pre-computed 32 byte digest
+pub fn recover_digest(digest: &hash::Hash256b, signature: &[u8]) -> Result<[u8; 65], Error> {
+    if signature.len() != 65 {
+        return Err(Error::InvalidSignature);
+    }
+    let msg = Message::from_slice(&digest.0).expect("32 bytes");
+    let rec_id =
+        RecoveryId::from_i32(signature[64] as i32).map_err(|_err| Error::InvalidSignature)?;
+    let sig = RecoverableSignature::from_compact(&signature[0..64], rec_id)
+        .map_err(|_err| Error::InvalidSignature)?;
+    let rec_pubkey = &SECP256K1
+        .recover(&msg, &sig)
+        .map_err(|_err| Error::InvalidSignature)?;
     Ok(rec_pubkey.serialize_uncompressed())
 }
 
@@ -110,10 +195,10 @@ fn test_from_slice() {
         &mut bytes as &mut [u8],
     )
     .unwrap();
-    let key = PrivKey::from_slice(&bytes);
+    let key = PrivKey::from_slice(&bytes).unwrap();
 
     assert_eq!(
-        key.hex_string(),
+        key.export_hex(),
         String::from("0806c458b262edd333a191e92f561aff338211ee3e18ab315a074a2d82aa343f")
     );
 
@@ -137,7 +222,7 @@ fn test_from_slice() {
         hex::encode(&sig[..]),
         String::from("eadf42d2ed96045b6d0060d952d40292a3f04e49867b4b3f96ef14d9d727640d72e4b350b889739330e818eff7341343cc8c9e6d6560513113dde9bfe0d9efa700")
     );
-    assert_eq!(verify_sig(&bytes, &sig, key.public_key()).unwrap(), true);
+    assert!(verify_sig(&bytes, &sig, key.public_key()).unwrap());
 }
 
 #[test]
@@ -146,6 +231,11 @@ fn test_new() {
     assert_eq!(key1.public_key().len(), 130);
     let key2 = PrivKey::new();
     assert_ne!(key1.bytes, key2.bytes);
+    assert_eq!(format!("{:?}", key1), "PrivKey(<redacted>)");
+    assert_eq!(
+        key1.export_bytes()[..],
+        hex::decode(key1.export_hex()).unwrap()[..]
+    );
 }
 #[test]
 fn test_recover() {
@@ -169,4 +259,56 @@ fn test_recover() {
         pubkey,
         String::from("044e18306ae9ef4ec9d07bf6e705442d4d1a75e6cdf750330ca2d880f2cc54607c9c33deb9eae9c06e06e04fe9ce3d43962cc67d5aa34fbeb71270d4bad3d648d9")
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_digest() {
+    let key = PrivKey::new();
+    let data = b"IoTeX";
+    let digest = hash::hash256b(data);
+    let sig = key.sign_digest(&digest);
+    assert_eq!(sig[..], key.sign(data)[..]);
+    assert!(verify_digest(&digest, &sig, key.public_key()).unwrap());
+    assert!(!verify_digest(&hash::hash256b(b"other"), &sig, key.public_key()).unwrap());
+    assert_eq!(
+        hex::encode(&recover_digest(&digest, &sig).unwrap()[..]),
+        key.public_key()
+    );
+
+    assert_eq!(recover(&[0; 31], &sig), Err(Error::InvalidMessageLen(31)));
+    assert_eq!(recover(&digest.0, &sig[..64]), Err(Error::InvalidSignature));
+    assert_eq!(
+        verify_digest(&digest, &sig[..10], key.public_key()),
+        Err(Error::InvalidSignature)
+    );
+}
+
+#[test]
+fn test_invalid_private_key() {
+    // the secp256k1 curve order n and n - 1
+    let order = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+    let max = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140";
+    let invalid = [
+        String::from(order),
+        "f".repeat(64),
+        "0".repeat(64),
+        String::from("0806c458"),
+        String::from("zz06c458b262edd333a191e92f561aff338211ee3e18ab315a074a2d82aa343f"),
+    ];
+    for s in invalid.iter() {
+        assert_eq!(
+            hex_string_to_private(s.clone()).err(),
+            Some(Error::InvalidPrivateKey)
+        );
+    }
+    assert_eq!(
+        PrivKey::from_slice(&[0; 32]).err(),
+        Some(Error::InvalidPrivateKey)
+    );
+    assert!(hex_string_to_private(String::from(max)).is_ok());
+
+    let key = "0806c458b262edd333a191e92f561aff338211ee3e18ab315a074a2d82aa343f";
+    for s in [format!("0x{}", key), format!("0X{}", key)].iter() {
+        assert_eq!(hex_string_to_private(s.clone()).unwrap().export_hex(), key);
+    }
+}
@@ -1,4 +1,15 @@
+extern crate self as iotex_antenna_rust;
+
 pub mod account;
+pub mod action;
 pub mod address;
+pub mod chain;
 pub mod contract;
 pub mod crypto;
+pub mod rpc;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod token;
+
+pub use ethabi;
+pub use iotex_antenna_macros::abigen;
@@ -0,0 +1,84 @@
+// MockClient is an in-memory Client used by unit tests
+use super::*;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+pub struct MockClient {
+    pub nonce: Cell<u64>,
+    pub reads: RefCell<Vec<Execution>>,
+    pub responses: RefCell<VecDeque<Result<ReadContractResponse, RpcError>>>,
+    pub sent: RefCell<Vec<SealedEnvelope>>,
+    pub receipt: RefCell<Option<Receipt>>,
+}
+
+impl MockClient {
+    pub fn new() -> Self {
+        MockClient {
+            nonce: Cell::new(0),
+            reads: RefCell::new(Vec::new()),
+            responses: RefCell::new(VecDeque::new()),
+            sent: RefCell::new(Vec::new()),
+            receipt: RefCell::new(None),
+        }
+    }
+    // respond queues the output of the next read_contract call
+    pub fn respond(&self, data: Vec<u8>) {
+        self.responses
+            .borrow_mut()
+            .push_back(Ok(ReadContractResponse {
+                data,
+                receipt: success_receipt(),
+            }));
+    }
+    // respond_receipt queues the receipt of the next read_contract call
+    pub fn respond_receipt(&self, data: Vec<u8>, receipt: Receipt) {
+        self.responses
+            .borrow_mut()
+            .push_back(Ok(ReadContractResponse { data, receipt }));
+    }
+    // mine sets the receipt returned for every sent action
+    pub fn mine(&self, receipt: Receipt) {
+        *self.receipt.borrow_mut() = Some(receipt);
+    }
+}
+
+pub fn success_receipt() -> Receipt {
+    Receipt {
+        status: crate::action::receipt::RECEIPT_STATUS_SUCCESS,
+        blk_height: 1,
+        act_hash: Hash256b([0; 32]),
+        gas_consumed: 10_000,
+        contract_address: String::new(),
+        logs: Vec::new(),
+        execution_revert_msg: String::new(),
+    }
+}
+
+impl Client for MockClient {
+    fn pending_nonce(&self, _addr: &AddrV1) -> Result<u64, RpcError> {
+        Ok(self.nonce.get())
+    }
+    fn read_contract(
+        &self,
+        execution: &Execution,
+        _caller: &AddrV1,
+        _gas_limit: u64,
+    ) -> Result<ReadContractResponse, RpcError> {
+        self.reads.borrow_mut().push(execution.clone());
+        match self.responses.borrow_mut().pop_front() {
+            Some(r) => r,
+            None => Err(RpcError::Server(String::from("no response queued"))),
+        }
+    }
+    fn send_action(&self, action: &SealedEnvelope) -> Result<Hash256b, RpcError> {
+        self.nonce.set(self.nonce.get() + 1);
+        self.sent.borrow_mut().push(action.clone());
+        Ok(action.hash())
+    }
+    fn get_receipt(&self, hash: &Hash256b) -> Result<Option<Receipt>, RpcError> {
+        Ok(self.receipt.borrow().clone().map(|mut r| {
+            r.act_hash = *hash;
+            r
+        }))
+    }
+}
@@ -0,0 +1,116 @@
+use super::{
+    action::{Execution, Receipt, SealedEnvelope},
+    address::v1::AddrV1,
+    crypto::hash::Hash256b,
+};
+use std::{error, fmt, thread, time::Duration};
+
+#[cfg(test)]
+pub(crate) mod mock;
+
+// ReadContractResponse is the outcome of a read-only contract execution
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadContractResponse {
+    pub data: Vec<u8>,
+    pub receipt: Receipt,
+}
+
+// Client is the subset of the IoTeX API used by contracts and accounts.
+// It is implemented on top of whichever transport (gRPC, HTTP gateway, ...)
+// the application uses to reach an IoTeX node.
+pub trait Client {
+    // pending_nonce returns the nonce of the next action sent by addr
+    fn pending_nonce(&self, addr: &AddrV1) -> Result<u64, RpcError>;
+    // read_contract runs an execution against the latest state without committing it
+    fn read_contract(
+        &self,
+        execution: &Execution,
+        caller: &AddrV1,
+        gas_limit: u64,
+    ) -> Result<ReadContractResponse, RpcError>;
+    // send_action submits a signed action and returns its hash
+    fn send_action(&self, action: &SealedEnvelope) -> Result<Hash256b, RpcError>;
+    // get_receipt returns the receipt of an action, or None if it is not mined yet
+    fn get_receipt(&self, hash: &Hash256b) -> Result<Option<Receipt>, RpcError>;
+}
+
+impl<C: Client + ?Sized> Client for &C {
+    fn pending_nonce(&self, addr: &AddrV1) -> Result<u64, RpcError> {
+        (**self).pending_nonce(addr)
+    }
+    fn read_contract(
+        &self,
+        execution: &Execution,
+        caller: &AddrV1,
+        gas_limit: u64,
+    ) -> Result<ReadContractResponse, RpcError> {
+        (**self).read_contract(execution, caller, gas_limit)
+    }
+    fn send_action(&self, action: &SealedEnvelope) -> Result<Hash256b, RpcError> {
+        (**self).send_action(action)
+    }
+    fn get_receipt(&self, hash: &Hash256b) -> Result<Option<Receipt>, RpcError> {
+        (**self).get_receipt(hash)
+    }
+}
+
+// PendingReceipt is a handle on a sent action whose receipt may not exist yet
+pub struct PendingReceipt<'a, C: Client + ?Sized> {
+    client: &'a C,
+    hash: Hash256b,
+}
+
+impl<'a, C: Client + ?Sized> PendingReceipt<'a, C> {
+    pub fn new(client: &'a C, hash: Hash256b) -> Self {
+        PendingReceipt { client, hash }
+    }
+    // hash returns the hash of the sent action
+    pub fn hash(&self) -> Hash256b {
+        self.hash
+    }
+    // poll fetches the receipt once, returning None if it is not mined yet
+    pub fn poll(&self) -> Result<Option<Receipt>, RpcError> {
+        self.client.get_receipt(&self.hash)
+    }
+    // wait polls the receipt up to attempts times, sleeping interval in between
+    pub fn wait(&self, attempts: usize, interval: Duration) -> Result<Receipt, RpcError> {
+        for i in 0..attempts {
+            if let Some(receipt) = self.poll()? {
+                return Ok(receipt);
+            }
+            if i + 1 < attempts {
+                thread::sleep(interval);
+            }
+        }
+        Err(RpcError::ReceiptTimeout(self.hash))
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum RpcError {
+    Transport(String),
+    Server(String),
+    ReceiptTimeout(Hash256b),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcError::Transport(msg) => write!(f, "transport error, {}", msg),
+            RpcError::Server(msg) => write!(f, "server error, {}", msg),
+            RpcError::ReceiptTimeout(h) => {
+                write!(f, "receipt of action {} not found", hex::encode(h.0))
+            }
+        }
+    }
+}
+
+impl error::Error for RpcError {
+    fn description(&self) -> &str {
+        match *self {
+            RpcError::Transport(_) => "transport error",
+            RpcError::Server(_) => "server error",
+            RpcError::ReceiptTimeout(_) => "receipt not found",
+        }
+    }
+}
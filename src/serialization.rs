@@ -0,0 +1,262 @@
+// serde support, enabled by the serde feature. Addresses serialize as io1
+// bech32 strings, hashes, byte strings and signatures as hex and big numbers as
+// decimal strings. Hex input may carry a 0x prefix, and addresses can be read
+// from either form. The modules below can be used with #[serde(with = ...)] to
+// pick another representation for a field.
+use crate::address::{self, v1::AddrV1, Address};
+use crate::crypto::hash::{Hash160b, Hash256b};
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+impl Serialize for AddrV1 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.string())
+    }
+}
+
+impl<'de> Deserialize<'de> for AddrV1 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        parse_address(&String::deserialize(deserializer)?)
+    }
+}
+
+impl Serialize for Hash160b {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash160b {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Hash160b(hex_array::deserialize(deserializer)?))
+    }
+}
+
+impl Serialize for Hash256b {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash256b {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Hash256b(hex_array::deserialize(deserializer)?))
+    }
+}
+
+// address_hex represents an address as 0x-prefixed hex of its 20 bytes
+pub mod address_hex {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(addr: &AddrV1, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(addr.bytes())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<AddrV1, D::Error> {
+        AddrV1::deserialize(deserializer)
+    }
+}
+
+// address_string represents an address held in a String field, e.g. the
+// contract of an execution, as io1 bech32; input can be either form and the
+// empty string, which stands for no address, is kept
+pub mod address_string {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(addr: &str, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(addr)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s.is_empty() {
+            return Ok(s);
+        }
+        Ok(parse_address(&s)?.string())
+    }
+}
+
+// address_string_hex is address_string writing 0x-prefixed hex instead
+pub mod address_string_hex {
+    use super::*;
+    use serde::ser;
+
+    pub fn serialize<S: Serializer>(addr: &str, serializer: S) -> Result<S::Ok, S::Error> {
+        if addr.is_empty() {
+            return serializer.serialize_str(addr);
+        }
+        match address::from_string(addr) {
+            Ok(r) => address_hex::serialize(&r, serializer),
+            Err(e) => Err(ser::Error::custom(format!(
+                "invalid address {}, {}",
+                addr, e
+            ))),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        address_string::deserialize(deserializer)
+    }
+}
+
+// hex_bytes represents a byte string as hex
+pub mod hex_bytes {
+    use super::*;
+
+    pub fn serialize<T: AsRef<[u8]>, S: Serializer>(
+        bytes: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes.as_ref()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        decode_hex(strip_hex_prefix(&s).unwrap_or(&s))
+    }
+}
+
+// hex_array represents a fixed size byte array, e.g. a signature, as hex
+pub mod hex_array {
+    use super::*;
+    use std::convert::TryInto;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        bytes: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let bytes = hex_bytes::deserialize(deserializer)?;
+        let len = bytes.len();
+        bytes
+            .try_into()
+            .map_err(|_| de::Error::invalid_length(len, &format!("{} bytes", N).as_str()))
+    }
+}
+
+// decimal represents a 256-bit unsigned integer as a decimal string, as the
+// IoTeX API does for amounts and gas prices
+pub mod decimal {
+    use super::*;
+    use ethabi::Uint;
+
+    pub fn serialize<S: Serializer>(value: &Uint, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Uint, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Uint::from_dec_str(&s).map_err(|_| de::Error::custom(format!("invalid number {}", s)))
+    }
+}
+
+// parse_address parses an io1 or 0x-prefixed hex address
+fn parse_address<E: de::Error>(s: &str) -> Result<AddrV1, E> {
+    let addr = match strip_hex_prefix(s) {
+        Some(h) => address::from_bytes(&decode_hex::<E>(h)?),
+        None => address::from_string(s),
+    };
+    addr.map_err(|e| E::custom(format!("invalid address {}, {}", s, e)))
+}
+
+fn strip_hex_prefix(s: &str) -> Option<&str> {
+    s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))
+}
+
+fn decode_hex<E: de::Error>(s: &str) -> Result<Vec<u8>, E> {
+    hex::decode(s).map_err(|e| E::custom(format!("invalid hex, {}", e)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::account::account;
+    use crate::action::{Envelope, Execution, Receipt, SealedEnvelope, VERSION};
+    use ethabi::Uint;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Transfer {
+        from: AddrV1,
+        #[serde(with = "address_hex")]
+        to: AddrV1,
+        #[serde(with = "address_string_hex")]
+        contract: String,
+    }
+
+    #[test]
+    fn test_serde() {
+        let acc = account::new_account().unwrap();
+        let addr = acc.address();
+        let transfer = Transfer {
+            from: addr,
+            to: addr,
+            contract: addr.string(),
+        };
+        let value = serde_json::to_value(&transfer).unwrap();
+        let hex = format!("0x{}", hex::encode(addr.bytes()));
+        assert_eq!(
+            value,
+            json!({ "from": addr.string(), "to": hex, "contract": hex })
+        );
+        assert_eq!(serde_json::from_value::<Transfer>(value).unwrap(), transfer);
+        let swapped = json!({ "from": hex, "to": addr.string(), "contract": addr.string() });
+        assert_eq!(
+            serde_json::from_value::<Transfer>(swapped).unwrap(),
+            transfer
+        );
+        assert!(serde_json::from_value::<AddrV1>(json!("io1invalid")).is_err());
+
+        let hash: Hash256b =
+            serde_json::from_value(json!(format!("0x{}", "ab".repeat(32)))).unwrap();
+        assert_eq!(serde_json::to_value(hash).unwrap(), json!("ab".repeat(32)));
+        assert!(serde_json::from_value::<Hash160b>(json!("ab".repeat(32))).is_err());
+
+        let sealed = Envelope {
+            version: VERSION,
+            nonce: 2,
+            gas_limit: 10_000,
+            gas_price: Uint::from_dec_str("1000000000000").unwrap(),
+            chain_id: 1,
+            execution: Execution {
+                amount: Uint::from(7),
+                contract: addr.string(),
+                data: vec![0xa9, 0x05],
+            },
+        }
+        .sign(&acc)
+        .unwrap();
+        let mut value = serde_json::to_value(&sealed).unwrap();
+        assert_eq!(value["envelope"]["gasPrice"], json!("1000000000000"));
+        assert_eq!(
+            value["envelope"]["execution"]["contract"],
+            json!(addr.string())
+        );
+        assert_eq!(value["envelope"]["execution"]["data"], json!("a905"));
+        assert_eq!(
+            value["signature"],
+            json!(hex::encode(&sealed.signature()[..]))
+        );
+        value["envelope"]["execution"]["contract"] = json!(hex);
+        let decoded: SealedEnvelope = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(decoded, sealed);
+        assert_eq!(decoded.sender().unwrap(), addr);
+        // the sender public key must be the one that signed
+        let other = account::new_account().unwrap();
+        for public_key in [other.public_key(), String::new()].iter() {
+            let mut forged = value.clone();
+            forged["senderPubKey"] = json!(public_key);
+            assert!(serde_json::from_value::<SealedEnvelope>(forged).is_err());
+        }
+
+        let receipt = crate::rpc::mock::success_receipt();
+        let value = serde_json::to_value(&receipt).unwrap();
+        assert_eq!(serde_json::from_value::<Receipt>(value).unwrap(), receipt);
+    }
+}
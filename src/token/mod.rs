@@ -0,0 +1,210 @@
+use super::{
+    address::v1::AddrV1,
+    contract::{token_to_address, Contract, ContractError},
+    rpc::Client,
+};
+use ethabi::{Token, Uint};
+use std::{error, fmt};
+
+pub mod xrc1155;
+pub mod xrc20;
+pub mod xrc721;
+
+// ERC165_INTERFACE is the ERC-165 interface ID of supportsInterface itself
+pub const ERC165_INTERFACE: [u8; 4] = [0x01, 0xff, 0xc9, 0xa7];
+// INVALID_INTERFACE is the interface ID no ERC-165 contract may support
+pub const INVALID_INTERFACE: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+
+// format_units formats value in base units as a decimal amount with decimals
+// fraction digits, e.g. 1500000 with 6 decimals is "1.5"
+pub fn format_units(value: Uint, decimals: u8) -> String {
+    let digits = value.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let padded = if digits.len() <= decimals {
+        format!("{}{}", "0".repeat(decimals + 1 - digits.len()), digits)
+    } else {
+        digits
+    };
+    let (int, frac) = padded.split_at(padded.len() - decimals);
+    let frac = frac.trim_end_matches('0');
+    if frac.is_empty() {
+        String::from(int)
+    } else {
+        format!("{}.{}", int, frac)
+    }
+}
+
+// parse_units parses a decimal amount into base units with decimals fraction
+// digits, e.g. "1.5" with 6 decimals is 1500000
+pub fn parse_units(amount: &str, decimals: u8) -> Result<Uint, UnitsError> {
+    let amount = amount.trim();
+    let (int, frac) = match amount.find('.') {
+        Some(i) => (&amount[..i], &amount[i + 1..]),
+        None => (amount, ""),
+    };
+    if (int.is_empty() && frac.is_empty())
+        || !int.chars().all(|c| c.is_ascii_digit())
+        || !frac.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(UnitsError::InvalidAmount);
+    }
+    let frac = frac.trim_end_matches('0');
+    if frac.len() > decimals as usize {
+        return Err(UnitsError::TooManyDecimals(frac.len()));
+    }
+    let digits = format!(
+        "{}{}{}",
+        int,
+        frac,
+        "0".repeat(decimals as usize - frac.len())
+    );
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(Uint::zero());
+    }
+    match Uint::from_dec_str(digits) {
+        Ok(r) => Ok(r),
+        Err(_) => Err(UnitsError::Overflow),
+    }
+}
+
+// output takes the single output of a call
+fn output(mut tokens: Vec<Token>) -> Result<Token, ContractError> {
+    match tokens.pop() {
+        Some(t) if tokens.is_empty() => Ok(t),
+        _ => Err(invalid_data()),
+    }
+}
+
+fn uint_output(tokens: Vec<Token>) -> Result<Uint, ContractError> {
+    match output(tokens)? {
+        Token::Uint(v) => Ok(v),
+        _ => Err(invalid_data()),
+    }
+}
+
+fn string_output(tokens: Vec<Token>) -> Result<String, ContractError> {
+    match output(tokens)? {
+        Token::String(v) => Ok(v),
+        _ => Err(invalid_data()),
+    }
+}
+
+fn bool_output(tokens: Vec<Token>) -> Result<bool, ContractError> {
+    match output(tokens)? {
+        Token::Bool(v) => Ok(v),
+        _ => Err(invalid_data()),
+    }
+}
+
+fn address_output(tokens: Vec<Token>) -> Result<AddrV1, ContractError> {
+    token_to_address(&output(tokens)?)
+}
+
+fn uints_output(tokens: Vec<Token>) -> Result<Vec<Uint>, ContractError> {
+    match output(tokens)? {
+        Token::Array(values) => values.into_iter().map(|v| uint_value(&v)).collect(),
+        _ => Err(invalid_data()),
+    }
+}
+
+fn uint_value(token: &Token) -> Result<Uint, ContractError> {
+    match token {
+        Token::Uint(v) => Ok(*v),
+        _ => Err(invalid_data()),
+    }
+}
+
+// supports_interface asks the contract whether it implements interface_id;
+// its ABI must declare supportsInterface(bytes4)
+fn supports_interface<C: Client>(
+    contract: &Contract<C>,
+    interface_id: [u8; 4],
+) -> Result<bool, ContractError> {
+    let params = [Token::FixedBytes(interface_id.to_vec())];
+    bool_output(contract.call("supportsInterface", &params)?)
+}
+
+// detect_interface runs the ERC-165 detection of interface_id: the contract must
+// support ERC-165 itself and reject 0xffffffff. Contracts without
+// supportsInterface revert, which counts as not supporting the interface.
+fn detect_interface<C: Client>(
+    contract: &Contract<C>,
+    interface_id: [u8; 4],
+) -> Result<bool, ContractError> {
+    for (id, expected) in [
+        (ERC165_INTERFACE, true),
+        (INVALID_INTERFACE, false),
+        (interface_id, true),
+    ]
+    .iter()
+    {
+        match supports_interface(contract, *id) {
+            Ok(r) if r == *expected => (),
+            Ok(_) | Err(ContractError::AbiError(_)) => return Ok(false),
+            Err(ContractError::ExecutionFailed(_)) | Err(ContractError::Reverted(_, _)) => {
+                return Ok(false)
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn invalid_data() -> ContractError {
+    ContractError::AbiError(ethabi::ErrorKind::InvalidData.into())
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum UnitsError {
+    InvalidAmount,
+    TooManyDecimals(usize),
+    Overflow,
+}
+
+impl fmt::Display for UnitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UnitsError::InvalidAmount => write!(f, "invalid amount"),
+            UnitsError::TooManyDecimals(n) => write!(f, "too many decimals ({})", n),
+            UnitsError::Overflow => write!(f, "amount overflows 256 bits"),
+        }
+    }
+}
+
+impl error::Error for UnitsError {
+    fn description(&self) -> &str {
+        match *self {
+            UnitsError::InvalidAmount => "invalid amount",
+            UnitsError::TooManyDecimals(_) => "too many decimals",
+            UnitsError::Overflow => "amount overflows 256 bits",
+        }
+    }
+}
+
+#[test]
+fn test_units() {
+    let tests: [(u64, u8, &str); 6] = [
+        (1_500_000, 6, "1.5"),
+        (1, 18, "0.000000000000000001"),
+        (0, 18, "0"),
+        (42, 0, "42"),
+        (100, 2, "1"),
+        (123_456, 3, "123.456"),
+    ];
+    for test in tests.iter() {
+        assert_eq!(format_units(Uint::from(test.0), test.1), test.2);
+        assert_eq!(parse_units(test.2, test.1).unwrap(), Uint::from(test.0));
+    }
+    assert_eq!(parse_units(".5", 1).unwrap(), Uint::from(5));
+    assert_eq!(parse_units("2.", 1).unwrap(), Uint::from(20));
+    assert_eq!(parse_units("1.50", 1).unwrap(), Uint::from(15));
+    assert_eq!(parse_units("1.05", 1), Err(UnitsError::TooManyDecimals(2)));
+    assert_eq!(parse_units("1e3", 18), Err(UnitsError::InvalidAmount));
+    assert_eq!(parse_units(".", 18), Err(UnitsError::InvalidAmount));
+    assert_eq!(parse_units("-1", 18), Err(UnitsError::InvalidAmount));
+    assert_eq!(parse_units(&"9".repeat(80), 0), Err(UnitsError::Overflow));
+}
@@ -0,0 +1,426 @@
+use super::xrc721::{approval_for_all_events, ApprovalForAll};
+use super::*;
+use crate::{
+    account::{address_book::ToAddress, Signer},
+    action::Receipt,
+    contract::Event,
+    rpc::PendingReceipt,
+};
+
+// XRC1155_INTERFACE is the ERC-165 interface ID of XRC1155 (ERC1155)
+pub const XRC1155_INTERFACE: [u8; 4] = [0xd9, 0xb6, 0x7a, 0x26];
+// XRC1155_METADATA_URI_INTERFACE is the ERC-165 interface ID of uri
+pub const XRC1155_METADATA_URI_INTERFACE: [u8; 4] = [0x0e, 0x89, 0x34, 0x1c];
+
+// XRC1155_ABI is the ABI of the XRC1155 (ERC1155) multi token standard with
+// its metadata URI extension
+pub const XRC1155_ABI: &str = r#"[
+    {"type":"function","name":"supportsInterface","constant":true,
+     "inputs":[{"name":"interfaceId","type":"bytes4"}],
+     "outputs":[{"name":"","type":"bool"}]},
+    {"type":"function","name":"uri","constant":true,
+     "inputs":[{"name":"id","type":"uint256"}],
+     "outputs":[{"name":"","type":"string"}]},
+    {"type":"function","name":"balanceOf","constant":true,
+     "inputs":[{"name":"account","type":"address"},{"name":"id","type":"uint256"}],
+     "outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"balanceOfBatch","constant":true,
+     "inputs":[{"name":"accounts","type":"address[]"},{"name":"ids","type":"uint256[]"}],
+     "outputs":[{"name":"","type":"uint256[]"}]},
+    {"type":"function","name":"isApprovedForAll","constant":true,
+     "inputs":[{"name":"account","type":"address"},{"name":"operator","type":"address"}],
+     "outputs":[{"name":"","type":"bool"}]},
+    {"type":"function","name":"setApprovalForAll","constant":false,
+     "inputs":[{"name":"operator","type":"address"},{"name":"approved","type":"bool"}],
+     "outputs":[]},
+    {"type":"function","name":"safeTransferFrom","constant":false,
+     "inputs":[{"name":"from","type":"address"},{"name":"to","type":"address"},
+               {"name":"id","type":"uint256"},{"name":"amount","type":"uint256"},
+               {"name":"data","type":"bytes"}],
+     "outputs":[]},
+    {"type":"function","name":"safeBatchTransferFrom","constant":false,
+     "inputs":[{"name":"from","type":"address"},{"name":"to","type":"address"},
+               {"name":"ids","type":"uint256[]"},{"name":"amounts","type":"uint256[]"},
+               {"name":"data","type":"bytes"}],
+     "outputs":[]},
+    {"type":"event","name":"TransferSingle","anonymous":false,
+     "inputs":[{"name":"operator","type":"address","indexed":true},
+               {"name":"from","type":"address","indexed":true},
+               {"name":"to","type":"address","indexed":true},
+               {"name":"id","type":"uint256","indexed":false},
+               {"name":"value","type":"uint256","indexed":false}]},
+    {"type":"event","name":"TransferBatch","anonymous":false,
+     "inputs":[{"name":"operator","type":"address","indexed":true},
+               {"name":"from","type":"address","indexed":true},
+               {"name":"to","type":"address","indexed":true},
+               {"name":"ids","type":"uint256[]","indexed":false},
+               {"name":"values","type":"uint256[]","indexed":false}]},
+    {"type":"event","name":"ApprovalForAll","anonymous":false,
+     "inputs":[{"name":"account","type":"address","indexed":true},
+               {"name":"operator","type":"address","indexed":true},
+               {"name":"approved","type":"bool","indexed":false}]},
+    {"type":"event","name":"URI","anonymous":false,
+     "inputs":[{"name":"value","type":"string","indexed":false},
+               {"name":"id","type":"uint256","indexed":true}]}
+]"#;
+
+// TransferSingle is a decoded XRC1155 TransferSingle event
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TransferSingle {
+    pub operator: AddrV1,
+    pub from: AddrV1,
+    pub to: AddrV1,
+    pub id: Uint,
+    pub value: Uint,
+}
+
+// TransferBatch is a decoded XRC1155 TransferBatch event
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferBatch {
+    pub operator: AddrV1,
+    pub from: AddrV1,
+    pub to: AddrV1,
+    pub ids: Vec<Uint>,
+    pub values: Vec<Uint>,
+}
+
+// Uri is a decoded XRC1155 URI event
+#[derive(Clone, Debug, PartialEq)]
+pub struct Uri {
+    pub value: String,
+    pub id: Uint,
+}
+
+// Xrc1155 is an XRC1155 multi token contract
+pub struct Xrc1155<C: Client> {
+    contract: Contract<C>,
+}
+
+impl<C: Client> Xrc1155<C> {
+    // new binds the token deployed at address; transactions are sent by signer
+    pub fn new<S: Signer + 'static>(
+        address: AddrV1,
+        client: C,
+        signer: S,
+    ) -> Result<Xrc1155<C>, ContractError> {
+        Ok(Xrc1155 {
+            contract: Contract::new(address, XRC1155_ABI, client, signer)?,
+        })
+    }
+    // connect binds the token like new, after checking through ERC-165 that the
+    // contract implements XRC1155
+    pub fn connect<S: Signer + 'static>(
+        address: AddrV1,
+        client: C,
+        signer: S,
+    ) -> Result<Xrc1155<C>, ContractError> {
+        let token = Xrc1155::new(address, client, signer)?;
+        if !token.supports_interface(XRC1155_INTERFACE)? {
+            return Err(ContractError::InterfaceNotSupported(XRC1155_INTERFACE));
+        }
+        Ok(token)
+    }
+    // contract returns the underlying contract
+    pub fn contract(&self) -> &Contract<C> {
+        &self.contract
+    }
+    // contract_mut returns the underlying contract, e.g. to tune gas
+    pub fn contract_mut(&mut self) -> &mut Contract<C> {
+        &mut self.contract
+    }
+    // supports_interface tells through ERC-165 whether the token implements interface_id
+    pub fn supports_interface(&self, interface_id: [u8; 4]) -> Result<bool, ContractError> {
+        detect_interface(&self.contract, interface_id)
+    }
+    // uri returns the metadata URI template of id, which may contain {id}
+    pub fn uri(&self, id: Uint) -> Result<String, ContractError> {
+        string_output(self.contract.call("uri", &[Token::Uint(id)])?)
+    }
+    // token_uri returns the metadata URI of id, with {id} substituted
+    pub fn token_uri(&self, id: Uint) -> Result<String, ContractError> {
+        Ok(substitute_id(&self.uri(id)?, id))
+    }
+    // balance_of returns the amount of id held by owner
+    pub fn balance_of<A: ToAddress>(&self, owner: A, id: Uint) -> Result<Uint, ContractError> {
+        let params = [self.contract.address_token(owner)?, Token::Uint(id)];
+        uint_output(self.contract.call("balanceOf", &params)?)
+    }
+    // balance_of_batch returns the balance of each (owner, id) pair
+    pub fn balance_of_batch<A: ToAddress>(
+        &self,
+        owners: &[A],
+        ids: &[Uint],
+    ) -> Result<Vec<Uint>, ContractError> {
+        if owners.len() != ids.len() {
+            return Err(invalid_data());
+        }
+        let params = [
+            Token::Array(
+                owners
+                    .iter()
+                    .map(|o| self.contract.address_token(o))
+                    .collect::<Result<_, _>>()?,
+            ),
+            uints_token(ids),
+        ];
+        let balances = uints_output(self.contract.call("balanceOfBatch", &params)?)?;
+        if balances.len() != ids.len() {
+            return Err(invalid_data());
+        }
+        Ok(balances)
+    }
+    // is_approved_for_all tells whether operator manages all tokens of owner
+    pub fn is_approved_for_all<A: ToAddress, B: ToAddress>(
+        &self,
+        owner: A,
+        operator: B,
+    ) -> Result<bool, ContractError> {
+        let params = [
+            self.contract.address_token(owner)?,
+            self.contract.address_token(operator)?,
+        ];
+        bool_output(self.contract.call("isApprovedForAll", &params)?)
+    }
+    // set_approval_for_all allows or forbids operator to manage all tokens of the account
+    pub fn set_approval_for_all<A: ToAddress>(
+        &self,
+        operator: A,
+        approved: bool,
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        let params = [
+            self.contract.address_token(operator)?,
+            Token::Bool(approved),
+        ];
+        self.contract
+            .execute("setApprovalForAll", &params, Uint::zero())
+    }
+    // safe_transfer_from moves amount of id from from to to; data is passed to
+    // the onERC1155Received hook of a contract receiver
+    pub fn safe_transfer_from<A: ToAddress, B: ToAddress>(
+        &self,
+        from: A,
+        to: B,
+        id: Uint,
+        amount: Uint,
+        data: &[u8],
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        let params = [
+            self.contract.address_token(from)?,
+            self.contract.address_token(to)?,
+            Token::Uint(id),
+            Token::Uint(amount),
+            Token::Bytes(data.to_vec()),
+        ];
+        self.contract
+            .execute("safeTransferFrom", &params, Uint::zero())
+    }
+    // safe_batch_transfer_from moves amounts[i] of ids[i] from from to to
+    pub fn safe_batch_transfer_from<A: ToAddress, B: ToAddress>(
+        &self,
+        from: A,
+        to: B,
+        ids: &[Uint],
+        amounts: &[Uint],
+        data: &[u8],
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        if ids.len() != amounts.len() {
+            return Err(invalid_data());
+        }
+        let params = [
+            self.contract.address_token(from)?,
+            self.contract.address_token(to)?,
+            uints_token(ids),
+            uints_token(amounts),
+            Token::Bytes(data.to_vec()),
+        ];
+        self.contract
+            .execute("safeBatchTransferFrom", &params, Uint::zero())
+    }
+    // transfer_single_events decodes the TransferSingle events of receipt
+    pub fn transfer_single_events(
+        &self,
+        receipt: &Receipt,
+    ) -> Result<Vec<TransferSingle>, ContractError> {
+        let mut events = Vec::new();
+        for event in self.events(receipt, "TransferSingle")? {
+            match event.params.as_slice() {
+                [operator, from, to, id, value] => events.push(TransferSingle {
+                    operator: token_to_address(&operator.value)?,
+                    from: token_to_address(&from.value)?,
+                    to: token_to_address(&to.value)?,
+                    id: uint_value(&id.value)?,
+                    value: uint_value(&value.value)?,
+                }),
+                _ => return Err(invalid_data()),
+            }
+        }
+        Ok(events)
+    }
+    // transfer_batch_events decodes the TransferBatch events of receipt
+    pub fn transfer_batch_events(
+        &self,
+        receipt: &Receipt,
+    ) -> Result<Vec<TransferBatch>, ContractError> {
+        let mut events = Vec::new();
+        for event in self.events(receipt, "TransferBatch")? {
+            match event.params.as_slice() {
+                [operator, from, to, ids, values] => events.push(TransferBatch {
+                    operator: token_to_address(&operator.value)?,
+                    from: token_to_address(&from.value)?,
+                    to: token_to_address(&to.value)?,
+                    ids: uints_output(vec![ids.value.clone()])?,
+                    values: uints_output(vec![values.value.clone()])?,
+                }),
+                _ => return Err(invalid_data()),
+            }
+        }
+        Ok(events)
+    }
+    // approval_for_all_events decodes the ApprovalForAll events of receipt
+    pub fn approval_for_all_events(
+        &self,
+        receipt: &Receipt,
+    ) -> Result<Vec<ApprovalForAll>, ContractError> {
+        approval_for_all_events(&self.contract, receipt)
+    }
+    // uri_events decodes the URI events of receipt
+    pub fn uri_events(&self, receipt: &Receipt) -> Result<Vec<Uri>, ContractError> {
+        let mut events = Vec::new();
+        for event in self.events(receipt, "URI")? {
+            match event.params.as_slice() {
+                [value, id] => events.push(Uri {
+                    value: string_output(vec![value.value.clone()])?,
+                    id: uint_value(&id.value)?,
+                }),
+                _ => return Err(invalid_data()),
+            }
+        }
+        Ok(events)
+    }
+
+    fn events(&self, receipt: &Receipt, name: &str) -> Result<Vec<Event>, ContractError> {
+        let mut events = self.contract.decode_logs(receipt)?;
+        events.retain(|e| e.name == name);
+        Ok(events)
+    }
+}
+
+// substitute_id replaces {id} in uri with the lowercase hex of id, zero padded
+// to 64 characters as the XRC1155 metadata extension requires
+pub fn substitute_id(uri: &str, id: Uint) -> String {
+    let hex = format!("{:x}", id);
+    uri.replace("{id}", &format!("{}{}", "0".repeat(64 - hex.len()), hex))
+}
+
+fn uints_token(values: &[Uint]) -> Token {
+    Token::Array(values.iter().map(|v| Token::Uint(*v)).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::action::Log;
+    use crate::address::{self, Address};
+    use crate::crypto::hash;
+    use crate::rpc::mock::{self, MockClient};
+
+    const TOKEN: &str = "io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j";
+
+    #[test]
+    fn test_xrc1155() {
+        let client = MockClient::new();
+        let addr = address::from_string(TOKEN).unwrap();
+        let acc = crate::account::account::new_account().unwrap();
+        let owner = acc.address();
+
+        for v in [true, false, true].iter() {
+            client.respond(ethabi::encode(&[Token::Bool(*v)]));
+        }
+        let token = Xrc1155::connect(addr, &client, acc).unwrap();
+        assert_eq!(
+            hex::encode(&client.reads.borrow()[2].data[4..8]),
+            "d9b67a26"
+        );
+
+        client.respond(ethabi::encode(&[Token::String(String::from(
+            "https://token-cdn-domain/{id}.json",
+        ))]));
+        assert_eq!(
+            token.token_uri(Uint::from(0x4cce0)).unwrap(),
+            "https://token-cdn-domain/000000000000000000000000000000000000000000000000000000000004cce0.json"
+        );
+
+        let ids = [Uint::from(1), Uint::from(2)];
+        client.respond(ethabi::encode(&[uints_token(&[
+            Uint::from(10),
+            Uint::from(20),
+        ])]));
+        assert_eq!(
+            token.balance_of_batch(&[owner, addr], &ids).unwrap(),
+            vec![Uint::from(10), Uint::from(20)]
+        );
+        assert!(token.balance_of_batch(&[owner], &ids).is_err());
+
+        token
+            .safe_transfer_from(owner, addr, ids[0], Uint::from(3), &[])
+            .unwrap();
+        token
+            .safe_batch_transfer_from(owner, addr, &ids, &[Uint::from(1), Uint::from(1)], &[])
+            .unwrap();
+        let selectors: Vec<String> = client
+            .sent
+            .borrow()
+            .iter()
+            .map(|s| hex::encode(&s.envelope().execution.data[..4]))
+            .collect();
+        assert_eq!(selectors, vec!["f242432a", "2eb2c2d6"]);
+
+        let topics = |name: &[u8]| {
+            vec![
+                hash::hash256b(name),
+                hash::bytes_to_hash256(owner.bytes()),
+                hash::bytes_to_hash256(owner.bytes()),
+                hash::bytes_to_hash256(addr.bytes()),
+            ]
+        };
+        let mut receipt = mock::success_receipt();
+        receipt.logs = vec![
+            Log {
+                contract_address: String::from(TOKEN),
+                topics: topics(b"TransferSingle(address,address,address,uint256,uint256)"),
+                data: ethabi::encode(&[Token::Uint(ids[0]), Token::Uint(Uint::from(3))]),
+                blk_height: 1,
+                act_hash: hash::Hash256b([0; 32]),
+                index: 0,
+            },
+            Log {
+                contract_address: String::from(TOKEN),
+                topics: topics(b"TransferBatch(address,address,address,uint256[],uint256[])"),
+                data: ethabi::encode(&[uints_token(&ids), uints_token(&ids)]),
+                blk_height: 1,
+                act_hash: hash::Hash256b([0; 32]),
+                index: 1,
+            },
+        ];
+        assert_eq!(
+            token.transfer_single_events(&receipt).unwrap(),
+            vec![TransferSingle {
+                operator: owner,
+                from: owner,
+                to: addr,
+                id: ids[0],
+                value: Uint::from(3)
+            }]
+        );
+        assert_eq!(
+            token.transfer_batch_events(&receipt).unwrap(),
+            vec![TransferBatch {
+                operator: owner,
+                from: owner,
+                to: addr,
+                ids: ids.to_vec(),
+                values: ids.to_vec()
+            }]
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use super::*;
+use crate::{
+    account::{address_book::ToAddress, Signer},
+    action::Receipt,
+    address::v1::AddrV1,
+    contract::{token_to_address, Contract, Event},
+    rpc::{Client, PendingReceipt},
+};
+
+// XRC20_ABI is the ABI of the XRC20 (ERC20) token standard
+pub const XRC20_ABI: &str = r#"[
+    {"type":"function","name":"name","constant":true,"inputs":[],
+     "outputs":[{"name":"","type":"string"}]},
+    {"type":"function","name":"symbol","constant":true,"inputs":[],
+     "outputs":[{"name":"","type":"string"}]},
+    {"type":"function","name":"decimals","constant":true,"inputs":[],
+     "outputs":[{"name":"","type":"uint8"}]},
+    {"type":"function","name":"totalSupply","constant":true,"inputs":[],
+     "outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"balanceOf","constant":true,
+     "inputs":[{"name":"owner","type":"address"}],
+     "outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"allowance","constant":true,
+     "inputs":[{"name":"owner","type":"address"},{"name":"spender","type":"address"}],
+     "outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"transfer","constant":false,
+     "inputs":[{"name":"to","type":"address"},{"name":"value","type":"uint256"}],
+     "outputs":[{"name":"","type":"bool"}]},
+    {"type":"function","name":"approve","constant":false,
+     "inputs":[{"name":"spender","type":"address"},{"name":"value","type":"uint256"}],
+     "outputs":[{"name":"","type":"bool"}]},
+    {"type":"function","name":"transferFrom","constant":false,
+     "inputs":[{"name":"from","type":"address"},{"name":"to","type":"address"},
+               {"name":"value","type":"uint256"}],
+     "outputs":[{"name":"","type":"bool"}]},
+    {"type":"event","name":"Transfer","anonymous":false,
+     "inputs":[{"name":"from","type":"address","indexed":true},
+               {"name":"to","type":"address","indexed":true},
+               {"name":"value","type":"uint256","indexed":false}]},
+    {"type":"event","name":"Approval","anonymous":false,
+     "inputs":[{"name":"owner","type":"address","indexed":true},
+               {"name":"spender","type":"address","indexed":true},
+               {"name":"value","type":"uint256","indexed":false}]}
+]"#;
+
+// Transfer is a decoded XRC20 Transfer event
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transfer {
+    pub from: AddrV1,
+    pub to: AddrV1,
+    pub value: Uint,
+}
+
+// Approval is a decoded XRC20 Approval event
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Approval {
+    pub owner: AddrV1,
+    pub spender: AddrV1,
+    pub value: Uint,
+}
+
+// Xrc20 is an XRC20 token contract
+pub struct Xrc20<C: Client> {
+    contract: Contract<C>,
+}
+
+impl<C: Client> Xrc20<C> {
+    // new binds the token deployed at address; transactions are sent by signer
+    pub fn new<S: Signer + 'static>(
+        address: AddrV1,
+        client: C,
+        signer: S,
+    ) -> Result<Xrc20<C>, ContractError> {
+        Ok(Xrc20 {
+            contract: Contract::new(address, XRC20_ABI, client, signer)?,
+        })
+    }
+    // contract returns the underlying contract
+    pub fn contract(&self) -> &Contract<C> {
+        &self.contract
+    }
+    // contract_mut returns the underlying contract, e.g. to tune gas
+    pub fn contract_mut(&mut self) -> &mut Contract<C> {
+        &mut self.contract
+    }
+    // name returns the token name
+    pub fn name(&self) -> Result<String, ContractError> {
+        string_output(self.contract.call("name", &[])?)
+    }
+    // symbol returns the token symbol
+    pub fn symbol(&self) -> Result<String, ContractError> {
+        string_output(self.contract.call("symbol", &[])?)
+    }
+    // decimals returns the number of decimals of the token amounts
+    pub fn decimals(&self) -> Result<u8, ContractError> {
+        let decimals = uint_output(self.contract.call("decimals", &[])?)?;
+        if decimals > Uint::from(u8::MAX) {
+            return Err(invalid_data());
+        }
+        Ok(decimals.low_u32() as u8)
+    }
+    // total_supply returns the amount of tokens in existence, in base units
+    pub fn total_supply(&self) -> Result<Uint, ContractError> {
+        uint_output(self.contract.call("totalSupply", &[])?)
+    }
+    // balance_of returns the balance of owner, in base units
+    pub fn balance_of<A: ToAddress>(&self, owner: A) -> Result<Uint, ContractError> {
+        uint_output(
+            self.contract
+                .call("balanceOf", &[self.contract.address_token(owner)?])?,
+        )
+    }
+    // allowance returns how much spender may still transfer on behalf of owner
+    pub fn allowance<A: ToAddress, B: ToAddress>(
+        &self,
+        owner: A,
+        spender: B,
+    ) -> Result<Uint, ContractError> {
+        let params = [
+            self.contract.address_token(owner)?,
+            self.contract.address_token(spender)?,
+        ];
+        uint_output(self.contract.call("allowance", &params)?)
+    }
+    // transfer sends value base units to to
+    pub fn transfer<A: ToAddress>(
+        &self,
+        to: A,
+        value: Uint,
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        let params = [self.contract.address_token(to)?, Token::Uint(value)];
+        self.contract.execute("transfer", &params, Uint::zero())
+    }
+    // approve allows spender to transfer up to value base units
+    pub fn approve<A: ToAddress>(
+        &self,
+        spender: A,
+        value: Uint,
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        let params = [self.contract.address_token(spender)?, Token::Uint(value)];
+        self.contract.execute("approve", &params, Uint::zero())
+    }
+    // transfer_from sends value base units from from to to, using the allowance
+    pub fn transfer_from<A: ToAddress, B: ToAddress>(
+        &self,
+        from: A,
+        to: B,
+        value: Uint,
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        let params = [
+            self.contract.address_token(from)?,
+            self.contract.address_token(to)?,
+            Token::Uint(value),
+        ];
+        self.contract.execute("transferFrom", &params, Uint::zero())
+    }
+    // transfer_events decodes the Transfer events of receipt emitted by the token
+    pub fn transfer_events(&self, receipt: &Receipt) -> Result<Vec<Transfer>, ContractError> {
+        let mut events = Vec::new();
+        for event in self.events(receipt, "Transfer")? {
+            let (from, to, value) = transfer_params(&event)?;
+            events.push(Transfer { from, to, value });
+        }
+        Ok(events)
+    }
+    // approval_events decodes the Approval events of receipt emitted by the token
+    pub fn approval_events(&self, receipt: &Receipt) -> Result<Vec<Approval>, ContractError> {
+        let mut events = Vec::new();
+        for event in self.events(receipt, "Approval")? {
+            let (owner, spender, value) = transfer_params(&event)?;
+            events.push(Approval {
+                owner,
+                spender,
+                value,
+            });
+        }
+        Ok(events)
+    }
+    fn events(&self, receipt: &Receipt, name: &str) -> Result<Vec<Event>, ContractError> {
+        let mut events = self.contract.decode_logs(receipt)?;
+        events.retain(|e| e.name == name);
+        Ok(events)
+    }
+}
+
+// transfer_params extracts the (address, address, uint256) params shared by
+// Transfer and Approval
+fn transfer_params(event: &Event) -> Result<(AddrV1, AddrV1, Uint), ContractError> {
+    match event.params.as_slice() {
+        [a, b, value] => match value.value {
+            Token::Uint(v) => Ok((token_to_address(&a.value)?, token_to_address(&b.value)?, v)),
+            _ => Err(invalid_data()),
+        },
+        _ => Err(invalid_data()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::account::{address_book::AddressBook, AccountError};
+    use crate::action::Log;
+    use crate::address::{self, Address};
+    use crate::contract::address_to_token;
+    use crate::crypto::hash;
+    use crate::rpc::mock::{self, MockClient};
+
+    const TOKEN: &str = "io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j";
+
+    #[test]
+    fn test_xrc20() {
+        let client = MockClient::new();
+        let addr = address::from_string(TOKEN).unwrap();
+        let acc = crate::account::account::new_account().unwrap();
+        let mut token = Xrc20::new(addr, &client, acc.clone()).unwrap();
+        let owner = acc.address();
+
+        client.respond(ethabi::encode(&[Token::String(String::from(
+            "IoTeX Token",
+        ))]));
+        client.respond(ethabi::encode(&[Token::String(String::from("IOTX"))]));
+        client.respond(ethabi::encode(&[Token::Uint(Uint::from(18))]));
+        client.respond(ethabi::encode(&[Token::Uint(Uint::from(1000))]));
+        client.respond(ethabi::encode(&[Token::Uint(Uint::from(3))]));
+        assert_eq!(token.name().unwrap(), "IoTeX Token");
+        assert_eq!(token.symbol().unwrap(), "IOTX");
+        assert_eq!(token.decimals().unwrap(), 18);
+        assert_eq!(token.total_supply().unwrap(), Uint::from(1000));
+        assert_eq!(token.allowance(owner, addr).unwrap(), Uint::from(3));
+        assert_eq!(
+            client.reads.borrow()[4].data[4..36],
+            ethabi::encode(&[address_to_token(&owner)])[..]
+        );
+
+        client.respond(ethabi::encode(&[Token::Uint(Uint::from(250))]));
+        assert_eq!(token.balance_of(owner).unwrap(), Uint::from(250));
+        let data = client.reads.borrow()[5].data.clone();
+        assert_eq!(hex::encode(&data[..4]), "70a08231");
+        assert_eq!(data[4..], ethabi::encode(&[address_to_token(&owner)])[..]);
+
+        client.respond(ethabi::encode(&[Token::Uint(Uint::from(6))]));
+        let decimals = token.decimals().unwrap();
+        assert_eq!(format_units(Uint::from(1_500_000), decimals), "1.5");
+        assert_eq!(parse_units("2.5", decimals), Ok(Uint::from(2_500_000)));
+
+        token.transfer(addr, Uint::from(1)).unwrap();
+        token.approve(addr, Uint::from(2)).unwrap();
+        token.transfer_from(owner, addr, Uint::from(3)).unwrap();
+        let selectors: Vec<String> = client
+            .sent
+            .borrow()
+            .iter()
+            .map(|s| hex::encode(&s.envelope().execution.data[..4]))
+            .collect();
+        assert_eq!(selectors, vec!["a9059cbb", "095ea7b3", "23b872dd"]);
+
+        // counterparties can be named by alias once the token has a resolver
+        let mut book = AddressBook::new();
+        book.add(addr, Some("shop")).unwrap();
+        token.contract_mut().set_resolver(book);
+        token.transfer("shop", Uint::from(4)).unwrap();
+        assert_eq!(
+            client.sent.borrow()[3].envelope().execution.data[4..36],
+            ethabi::encode(&[address_to_token(&addr)])[..]
+        );
+        assert!(matches!(
+            token.transfer("nobody", Uint::from(4)),
+            Err(ContractError::AccountError(AccountError::UnknownName(_)))
+        ));
+
+        let log = |name: &[u8], from: &AddrV1, to: &AddrV1, value: u64| Log {
+            contract_address: String::from(TOKEN),
+            topics: vec![
+                hash::hash256b(name),
+                hash::bytes_to_hash256(from.bytes()),
+                hash::bytes_to_hash256(to.bytes()),
+            ],
+            data: ethabi::encode(&[Token::Uint(Uint::from(value))]),
+            blk_height: 1,
+            act_hash: hash::Hash256b([0; 32]),
+            index: 0,
+        };
+        let mut receipt = mock::success_receipt();
+        receipt.logs = vec![
+            log(b"Transfer(address,address,uint256)", &owner, &addr, 5),
+            log(b"Approval(address,address,uint256)", &owner, &addr, 7),
+        ];
+        assert_eq!(
+            token.transfer_events(&receipt).unwrap(),
+            vec![Transfer {
+                from: owner,
+                to: addr,
+                value: Uint::from(5)
+            }]
+        );
+        assert_eq!(
+            token.approval_events(&receipt).unwrap(),
+            vec![Approval {
+                owner,
+                spender: addr,
+                value: Uint::from(7)
+            }]
+        );
+    }
+}
@@ -0,0 +1,471 @@
+use super::*;
+use crate::{
+    account::{address_book::ToAddress, Signer},
+    action::Receipt,
+    contract::Event,
+    rpc::PendingReceipt,
+};
+
+// XRC721_INTERFACE is the ERC-165 interface ID of XRC721 (ERC721)
+pub const XRC721_INTERFACE: [u8; 4] = [0x80, 0xac, 0x58, 0xcd];
+// XRC721_METADATA_INTERFACE is the ERC-165 interface ID of name, symbol and tokenURI
+pub const XRC721_METADATA_INTERFACE: [u8; 4] = [0x5b, 0x5e, 0x13, 0x9f];
+// XRC721_ENUMERABLE_INTERFACE is the ERC-165 interface ID of the enumeration extension
+pub const XRC721_ENUMERABLE_INTERFACE: [u8; 4] = [0x78, 0x0e, 0x9d, 0x63];
+
+// XRC721_ABI is the ABI of the XRC721 (ERC721) standard with its metadata and
+// enumeration extensions. Only the safeTransferFrom overload taking data is
+// declared, since ethabi keeps a single function per name.
+pub const XRC721_ABI: &str = r#"[
+    {"type":"function","name":"supportsInterface","constant":true,
+     "inputs":[{"name":"interfaceId","type":"bytes4"}],
+     "outputs":[{"name":"","type":"bool"}]},
+    {"type":"function","name":"name","constant":true,"inputs":[],
+     "outputs":[{"name":"","type":"string"}]},
+    {"type":"function","name":"symbol","constant":true,"inputs":[],
+     "outputs":[{"name":"","type":"string"}]},
+    {"type":"function","name":"tokenURI","constant":true,
+     "inputs":[{"name":"tokenId","type":"uint256"}],
+     "outputs":[{"name":"","type":"string"}]},
+    {"type":"function","name":"balanceOf","constant":true,
+     "inputs":[{"name":"owner","type":"address"}],
+     "outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"ownerOf","constant":true,
+     "inputs":[{"name":"tokenId","type":"uint256"}],
+     "outputs":[{"name":"","type":"address"}]},
+    {"type":"function","name":"getApproved","constant":true,
+     "inputs":[{"name":"tokenId","type":"uint256"}],
+     "outputs":[{"name":"","type":"address"}]},
+    {"type":"function","name":"isApprovedForAll","constant":true,
+     "inputs":[{"name":"owner","type":"address"},{"name":"operator","type":"address"}],
+     "outputs":[{"name":"","type":"bool"}]},
+    {"type":"function","name":"totalSupply","constant":true,"inputs":[],
+     "outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"tokenByIndex","constant":true,
+     "inputs":[{"name":"index","type":"uint256"}],
+     "outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"tokenOfOwnerByIndex","constant":true,
+     "inputs":[{"name":"owner","type":"address"},{"name":"index","type":"uint256"}],
+     "outputs":[{"name":"","type":"uint256"}]},
+    {"type":"function","name":"approve","constant":false,
+     "inputs":[{"name":"to","type":"address"},{"name":"tokenId","type":"uint256"}],
+     "outputs":[]},
+    {"type":"function","name":"setApprovalForAll","constant":false,
+     "inputs":[{"name":"operator","type":"address"},{"name":"approved","type":"bool"}],
+     "outputs":[]},
+    {"type":"function","name":"transferFrom","constant":false,
+     "inputs":[{"name":"from","type":"address"},{"name":"to","type":"address"},
+               {"name":"tokenId","type":"uint256"}],
+     "outputs":[]},
+    {"type":"function","name":"safeTransferFrom","constant":false,
+     "inputs":[{"name":"from","type":"address"},{"name":"to","type":"address"},
+               {"name":"tokenId","type":"uint256"},{"name":"data","type":"bytes"}],
+     "outputs":[]},
+    {"type":"event","name":"Transfer","anonymous":false,
+     "inputs":[{"name":"from","type":"address","indexed":true},
+               {"name":"to","type":"address","indexed":true},
+               {"name":"tokenId","type":"uint256","indexed":true}]},
+    {"type":"event","name":"Approval","anonymous":false,
+     "inputs":[{"name":"owner","type":"address","indexed":true},
+               {"name":"approved","type":"address","indexed":true},
+               {"name":"tokenId","type":"uint256","indexed":true}]},
+    {"type":"event","name":"ApprovalForAll","anonymous":false,
+     "inputs":[{"name":"owner","type":"address","indexed":true},
+               {"name":"operator","type":"address","indexed":true},
+               {"name":"approved","type":"bool","indexed":false}]}
+]"#;
+
+// Transfer is a decoded XRC721 Transfer event
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transfer {
+    pub from: AddrV1,
+    pub to: AddrV1,
+    pub token_id: Uint,
+}
+
+// Approval is a decoded XRC721 Approval event
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Approval {
+    pub owner: AddrV1,
+    pub approved: AddrV1,
+    pub token_id: Uint,
+}
+
+// ApprovalForAll is a decoded ApprovalForAll event, shared with XRC1155
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ApprovalForAll {
+    pub owner: AddrV1,
+    pub operator: AddrV1,
+    pub approved: bool,
+}
+
+// Xrc721 is an XRC721 non-fungible token contract
+pub struct Xrc721<C: Client> {
+    contract: Contract<C>,
+}
+
+impl<C: Client> Xrc721<C> {
+    // new binds the token deployed at address; transactions are sent by signer
+    pub fn new<S: Signer + 'static>(
+        address: AddrV1,
+        client: C,
+        signer: S,
+    ) -> Result<Xrc721<C>, ContractError> {
+        Ok(Xrc721 {
+            contract: Contract::new(address, XRC721_ABI, client, signer)?,
+        })
+    }
+    // connect binds the token like new, after checking through ERC-165 that the
+    // contract implements XRC721
+    pub fn connect<S: Signer + 'static>(
+        address: AddrV1,
+        client: C,
+        signer: S,
+    ) -> Result<Xrc721<C>, ContractError> {
+        let token = Xrc721::new(address, client, signer)?;
+        if !token.supports_interface(XRC721_INTERFACE)? {
+            return Err(ContractError::InterfaceNotSupported(XRC721_INTERFACE));
+        }
+        Ok(token)
+    }
+    // contract returns the underlying contract
+    pub fn contract(&self) -> &Contract<C> {
+        &self.contract
+    }
+    // contract_mut returns the underlying contract, e.g. to tune gas
+    pub fn contract_mut(&mut self) -> &mut Contract<C> {
+        &mut self.contract
+    }
+    // supports_interface tells through ERC-165 whether the token implements interface_id
+    pub fn supports_interface(&self, interface_id: [u8; 4]) -> Result<bool, ContractError> {
+        detect_interface(&self.contract, interface_id)
+    }
+    // name returns the collection name
+    pub fn name(&self) -> Result<String, ContractError> {
+        string_output(self.contract.call("name", &[])?)
+    }
+    // symbol returns the collection symbol
+    pub fn symbol(&self) -> Result<String, ContractError> {
+        string_output(self.contract.call("symbol", &[])?)
+    }
+    // token_uri returns the metadata URI of token_id
+    pub fn token_uri(&self, token_id: Uint) -> Result<String, ContractError> {
+        string_output(self.contract.call("tokenURI", &[Token::Uint(token_id)])?)
+    }
+    // balance_of returns how many tokens owner holds
+    pub fn balance_of<A: ToAddress>(&self, owner: A) -> Result<Uint, ContractError> {
+        uint_output(
+            self.contract
+                .call("balanceOf", &[self.contract.address_token(owner)?])?,
+        )
+    }
+    // owner_of returns the owner of token_id
+    pub fn owner_of(&self, token_id: Uint) -> Result<AddrV1, ContractError> {
+        address_output(self.contract.call("ownerOf", &[Token::Uint(token_id)])?)
+    }
+    // get_approved returns the address approved to transfer token_id
+    pub fn get_approved(&self, token_id: Uint) -> Result<AddrV1, ContractError> {
+        address_output(
+            self.contract
+                .call("getApproved", &[Token::Uint(token_id)])?,
+        )
+    }
+    // is_approved_for_all tells whether operator manages all tokens of owner
+    pub fn is_approved_for_all<A: ToAddress, B: ToAddress>(
+        &self,
+        owner: A,
+        operator: B,
+    ) -> Result<bool, ContractError> {
+        let params = [
+            self.contract.address_token(owner)?,
+            self.contract.address_token(operator)?,
+        ];
+        bool_output(self.contract.call("isApprovedForAll", &params)?)
+    }
+    // total_supply returns the number of tokens, from the enumeration extension
+    pub fn total_supply(&self) -> Result<Uint, ContractError> {
+        uint_output(self.contract.call("totalSupply", &[])?)
+    }
+    // token_by_index returns the index-th token, from the enumeration extension
+    pub fn token_by_index(&self, index: Uint) -> Result<Uint, ContractError> {
+        uint_output(self.contract.call("tokenByIndex", &[Token::Uint(index)])?)
+    }
+    // token_of_owner_by_index returns the index-th token of owner, from the
+    // enumeration extension
+    pub fn token_of_owner_by_index<A: ToAddress>(
+        &self,
+        owner: A,
+        index: Uint,
+    ) -> Result<Uint, ContractError> {
+        let params = [self.contract.address_token(owner)?, Token::Uint(index)];
+        uint_output(self.contract.call("tokenOfOwnerByIndex", &params)?)
+    }
+    // tokens_of_owner lists the tokens of owner through the enumeration extension
+    pub fn tokens_of_owner<A: ToAddress>(&self, owner: A) -> Result<Vec<Uint>, ContractError> {
+        let owner = self.contract.resolve(owner)?;
+        let balance = self.balance_of(owner)?;
+        let mut tokens = Vec::new();
+        let mut index = Uint::zero();
+        while index < balance {
+            tokens.push(self.token_of_owner_by_index(owner, index)?);
+            index += Uint::one();
+        }
+        Ok(tokens)
+    }
+    // approve allows to to transfer token_id
+    pub fn approve<A: ToAddress>(
+        &self,
+        to: A,
+        token_id: Uint,
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        let params = [self.contract.address_token(to)?, Token::Uint(token_id)];
+        self.contract.execute("approve", &params, Uint::zero())
+    }
+    // set_approval_for_all allows or forbids operator to manage all tokens of the account
+    pub fn set_approval_for_all<A: ToAddress>(
+        &self,
+        operator: A,
+        approved: bool,
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        let params = [
+            self.contract.address_token(operator)?,
+            Token::Bool(approved),
+        ];
+        self.contract
+            .execute("setApprovalForAll", &params, Uint::zero())
+    }
+    // transfer_from moves token_id from from to to, without receiver checks
+    pub fn transfer_from<A: ToAddress, B: ToAddress>(
+        &self,
+        from: A,
+        to: B,
+        token_id: Uint,
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        let params = [
+            self.contract.address_token(from)?,
+            self.contract.address_token(to)?,
+            Token::Uint(token_id),
+        ];
+        self.contract.execute("transferFrom", &params, Uint::zero())
+    }
+    // safe_transfer_from moves token_id from from to to, checking that a contract
+    // receiver accepts it; data is passed to its onERC721Received hook
+    pub fn safe_transfer_from<A: ToAddress, B: ToAddress>(
+        &self,
+        from: A,
+        to: B,
+        token_id: Uint,
+        data: &[u8],
+    ) -> Result<PendingReceipt<'_, C>, ContractError> {
+        let params = [
+            self.contract.address_token(from)?,
+            self.contract.address_token(to)?,
+            Token::Uint(token_id),
+            Token::Bytes(data.to_vec()),
+        ];
+        self.contract
+            .execute("safeTransferFrom", &params, Uint::zero())
+    }
+    // transfer_events decodes the Transfer events of receipt emitted by the token
+    pub fn transfer_events(&self, receipt: &Receipt) -> Result<Vec<Transfer>, ContractError> {
+        let mut events = Vec::new();
+        for event in self.events(receipt, "Transfer")? {
+            let (from, to, token_id) = id_params(&event)?;
+            events.push(Transfer { from, to, token_id });
+        }
+        Ok(events)
+    }
+    // approval_events decodes the Approval events of receipt emitted by the token
+    pub fn approval_events(&self, receipt: &Receipt) -> Result<Vec<Approval>, ContractError> {
+        let mut events = Vec::new();
+        for event in self.events(receipt, "Approval")? {
+            let (owner, approved, token_id) = id_params(&event)?;
+            events.push(Approval {
+                owner,
+                approved,
+                token_id,
+            });
+        }
+        Ok(events)
+    }
+    // approval_for_all_events decodes the ApprovalForAll events of receipt
+    pub fn approval_for_all_events(
+        &self,
+        receipt: &Receipt,
+    ) -> Result<Vec<ApprovalForAll>, ContractError> {
+        approval_for_all_events(&self.contract, receipt)
+    }
+
+    fn events(&self, receipt: &Receipt, name: &str) -> Result<Vec<Event>, ContractError> {
+        let mut events = self.contract.decode_logs(receipt)?;
+        events.retain(|e| e.name == name);
+        Ok(events)
+    }
+}
+
+// id_params extracts the (address, address, uint256) params of Transfer and Approval
+fn id_params(event: &Event) -> Result<(AddrV1, AddrV1, Uint), ContractError> {
+    match event.params.as_slice() {
+        [a, b, id] => Ok((
+            token_to_address(&a.value)?,
+            token_to_address(&b.value)?,
+            uint_value(&id.value)?,
+        )),
+        _ => Err(invalid_data()),
+    }
+}
+
+// approval_for_all_events decodes the ApprovalForAll events of an XRC721 or XRC1155 contract
+pub(super) fn approval_for_all_events<C: Client>(
+    contract: &Contract<C>,
+    receipt: &Receipt,
+) -> Result<Vec<ApprovalForAll>, ContractError> {
+    let mut events = Vec::new();
+    for event in contract.decode_logs(receipt)? {
+        if event.name != "ApprovalForAll" {
+            continue;
+        }
+        match event.params.as_slice() {
+            [owner, operator, approved] => match approved.value {
+                Token::Bool(approved) => events.push(ApprovalForAll {
+                    owner: token_to_address(&owner.value)?,
+                    operator: token_to_address(&operator.value)?,
+                    approved,
+                }),
+                _ => return Err(invalid_data()),
+            },
+            _ => return Err(invalid_data()),
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::action::Log;
+    use crate::address::{self, Address};
+    use crate::contract::address_to_token;
+    use crate::crypto::hash;
+    use crate::rpc::mock::{self, MockClient};
+
+    const TOKEN: &str = "io187wzp08vnhjjpkydnr97qlh8kh0dpkkytfam8j";
+
+    fn respond_bool(client: &MockClient, v: bool) {
+        client.respond(ethabi::encode(&[Token::Bool(v)]));
+    }
+
+    #[test]
+    fn test_connect() {
+        let client = MockClient::new();
+        let addr = address::from_string(TOKEN).unwrap();
+        let acc = crate::account::account::new_account().unwrap();
+
+        respond_bool(&client, true);
+        respond_bool(&client, false);
+        respond_bool(&client, true);
+        assert!(Xrc721::connect(addr, &client, acc.clone()).is_ok());
+        let ids: Vec<String> = client
+            .reads
+            .borrow()
+            .iter()
+            .map(|r| hex::encode(&r.data[4..8]))
+            .collect();
+        assert_eq!(ids, vec!["01ffc9a7", "ffffffff", "80ac58cd"]);
+
+        // a contract claiming every interface does not implement ERC-165
+        respond_bool(&client, true);
+        respond_bool(&client, true);
+        match Xrc721::connect(addr, &client, acc.clone()) {
+            Err(ContractError::InterfaceNotSupported(id)) => assert_eq!(id, XRC721_INTERFACE),
+            _ => panic!("interface should not be supported"),
+        }
+
+        // a contract without supportsInterface reverts
+        let mut failed = mock::success_receipt();
+        failed.status = 106;
+        client.respond_receipt(Vec::new(), failed);
+        assert!(Xrc721::connect(addr, &client, acc).is_err());
+    }
+
+    #[test]
+    fn test_xrc721() {
+        let client = MockClient::new();
+        let addr = address::from_string(TOKEN).unwrap();
+        let acc = crate::account::account::new_account().unwrap();
+        let token = Xrc721::new(addr, &client, acc.clone()).unwrap();
+        let owner = acc.address();
+
+        client.respond(ethabi::encode(&[address_to_token(&owner)]));
+        assert_eq!(token.owner_of(Uint::from(1)).unwrap(), owner);
+        client.respond(ethabi::encode(&[Token::String(String::from("ipfs://1"))]));
+        assert_eq!(token.token_uri(Uint::from(1)).unwrap(), "ipfs://1");
+        respond_bool(&client, true);
+        assert!(token.is_approved_for_all(owner, addr).unwrap());
+
+        client.respond(ethabi::encode(&[Token::Uint(Uint::from(2))]));
+        client.respond(ethabi::encode(&[Token::Uint(Uint::from(7))]));
+        client.respond(ethabi::encode(&[Token::Uint(Uint::from(9))]));
+        assert_eq!(
+            token.tokens_of_owner(owner).unwrap(),
+            vec![Uint::from(7), Uint::from(9)]
+        );
+
+        token
+            .safe_transfer_from(owner, addr, Uint::from(7), &[])
+            .unwrap();
+        token.set_approval_for_all(addr, true).unwrap();
+        let selectors: Vec<String> = client
+            .sent
+            .borrow()
+            .iter()
+            .map(|s| hex::encode(&s.envelope().execution.data[..4]))
+            .collect();
+        assert_eq!(selectors, vec!["b88d4fde", "a22cb465"]);
+
+        let mut receipt = mock::success_receipt();
+        receipt.logs = vec![
+            Log {
+                contract_address: String::from(TOKEN),
+                topics: vec![
+                    hash::hash256b(b"Transfer(address,address,uint256)"),
+                    hash::bytes_to_hash256(owner.bytes()),
+                    hash::bytes_to_hash256(addr.bytes()),
+                    hash::bytes_to_hash256(&[7]),
+                ],
+                data: Vec::new(),
+                blk_height: 1,
+                act_hash: hash::Hash256b([0; 32]),
+                index: 0,
+            },
+            Log {
+                contract_address: String::from(TOKEN),
+                topics: vec![
+                    hash::hash256b(b"ApprovalForAll(address,address,bool)"),
+                    hash::bytes_to_hash256(owner.bytes()),
+                    hash::bytes_to_hash256(addr.bytes()),
+                ],
+                data: ethabi::encode(&[Token::Bool(true)]),
+                blk_height: 1,
+                act_hash: hash::Hash256b([0; 32]),
+                index: 1,
+            },
+        ];
+        assert_eq!(
+            token.transfer_events(&receipt).unwrap(),
+            vec![Transfer {
+                from: owner,
+                to: addr,
+                token_id: Uint::from(7)
+            }]
+        );
+        assert_eq!(
+            token.approval_for_all_events(&receipt).unwrap(),
+            vec![ApprovalForAll {
+                owner,
+                operator: addr,
+                approved: true
+            }]
+        );
+    }
+}